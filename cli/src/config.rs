@@ -72,6 +72,29 @@ pub fn parse_pubkeys(pubkeys_str: &str) -> Result<Vec<Pubkey>> {
         .collect()
 }
 
+// Parse a duration like "30d", "12h", "2w" or plain seconds into seconds
+pub fn parse_duration(duration_str: &str) -> Result<i64> {
+    let duration_str = duration_str.trim();
+    let (value, unit) = match duration_str.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => duration_str.split_at(i),
+        None => (duration_str, "s"),
+    };
+    let value = i64::from_str(value)
+        .map_err(|_| anyhow!("Invalid duration: {}", duration_str))?;
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(anyhow!("Invalid duration unit in {} (use s, m, h, d or w)", duration_str)),
+    };
+    value
+        .checked_mul(multiplier)
+        .filter(|seconds| *seconds > 0)
+        .ok_or_else(|| anyhow!("Invalid duration: {}", duration_str))
+}
+
 // Convert SOL to lamports
 pub fn sol_to_lamports(sol: f64) -> u64 {
     (sol * 1_000_000_000.0) as u64
@@ -24,14 +24,70 @@ enum Commands {
         amount: f64,
         #[arg(short, long)]
         deadline: Option<i64>,
+        #[arg(long)]
+        late_fee: Option<f64>,
+        #[arg(long)]
+        late_fee_bps: Option<u64>,
+        #[arg(long)]
+        late_fee_per_day: bool,
+        #[arg(long)]
+        require_acceptance: bool,
+        #[arg(long)]
+        allow_withdrawals: bool,
+        #[arg(long)]
+        every: Option<String>,
+        #[arg(long)]
+        split_total: bool,
+        #[arg(long)]
+        mint: Option<String>,
+        #[arg(long)]
+        price_feed: Option<String>,
+        #[arg(long, default_value_t = 60)]
+        max_staleness: u32,
+        #[arg(long, default_value_t = 2.0)]
+        max_confidence_percent: f64,
+        #[arg(long)]
+        title: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        #[arg(long)]
+        category: Option<String>,
+        #[arg(long)]
+        reference: Option<String>,
+    },
+    EditJob {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(long)]
+        title: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        #[arg(long)]
+        category: Option<String>,
+        #[arg(long)]
+        reference: Option<String>,
     },
     Pay {
         #[arg(short, long)]
         job_id: String,
+        #[arg(short, long)]
+        memo: Option<String>,
+        #[arg(long)]
+        store_memo: bool,
+        #[arg(long)]
+        asset: Option<String>,
+    },
+    Prepay {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short = 'n', long)]
+        periods: u16,
     },
     Status {
         #[arg(short, long)]
         job_id: String,
+        #[arg(long)]
+        tree: bool,
     },
     Distribute {
         #[arg(short, long)]
@@ -42,7 +98,327 @@ enum Commands {
         job_id: String,
         #[arg(short, long)]
         output: Option<String>,
-    }
+    },
+    ExtendDeadline {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        deadline: i64,
+        #[arg(short, long)]
+        payer: Option<String>,
+    },
+    MarkSettled {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        payer: String,
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+    Roles {
+        #[command(subcommand)]
+        command: RoleCommands,
+    },
+    Payers {
+        #[command(subcommand)]
+        command: PayerCommands,
+    },
+    Hook {
+        #[command(subcommand)]
+        command: HookCommands,
+    },
+    Accept {
+        #[arg(short, long)]
+        job_id: String,
+    },
+    Decline {
+        #[arg(short, long)]
+        job_id: String,
+    },
+    Withdraw {
+        #[arg(short, long)]
+        job_id: String,
+    },
+    Group {
+        #[command(subcommand)]
+        command: GroupCommands,
+    },
+    Escrow {
+        #[command(subcommand)]
+        command: EscrowCommands,
+    },
+    Milestones {
+        #[command(subcommand)]
+        command: MilestoneCommands,
+    },
+    Vesting {
+        #[command(subcommand)]
+        command: VestingCommands,
+    },
+    Items {
+        #[command(subcommand)]
+        command: ItemCommands,
+    },
+    Assets {
+        #[command(subcommand)]
+        command: AssetCommands,
+    },
+    AddChild {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        child: String,
+    },
+    Sponsor {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        ratio_percent: f64,
+        #[arg(short, long)]
+        cap: f64,
+        #[arg(short, long)]
+        deposit: f64,
+        #[arg(short, long)]
+        sponsor_keypair: Option<String>,
+    },
+    SetCrankBounty {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        bounty: f64,
+    },
+    Crank {
+        #[arg(short, long)]
+        all: bool,
+    },
+    MockPrice {
+        #[arg(short, long)]
+        price: f64,
+        #[arg(short, long, default_value_t = 0.0)]
+        confidence: f64,
+        #[arg(long)]
+        publish_time: Option<i64>,
+        #[arg(short, long, default_value = "price-feed.json")]
+        output: String,
+        #[arg(short, long)]
+        address: Option<String>,
+    },
+    CreateProfile,
+    Profile {
+        wallet: Option<String>,
+    },
+    ReportLate {
+        #[arg(short, long)]
+        job_id: String,
+    },
+    SetApprovers {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        approvers: String,
+        #[arg(short, long)]
+        threshold: u8,
+    },
+    Approve {
+        #[arg(short, long)]
+        job_id: String,
+    },
+    Net {
+        #[arg(short, long)]
+        job_ids: String,
+        #[arg(short, long)]
+        execute: bool,
+        #[arg(short, long)]
+        signers: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RoleCommands {
+    Grant {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        wallet: String,
+        #[arg(short, long)]
+        roles: String,
+    },
+    Revoke {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        wallet: String,
+    },
+    List {
+        #[arg(short, long)]
+        job_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum PayerCommands {
+    Add {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        wallet: String,
+    },
+    Remove {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        wallet: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HookCommands {
+    Set {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        program: String,
+        #[arg(short, long)]
+        accounts: Option<String>,
+    },
+    Clear {
+        #[arg(short, long)]
+        job_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum EscrowCommands {
+    Enable {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        arbiter: String,
+        #[arg(short, long, default_value = "majority")]
+        confirmer: String,
+    },
+    Confirm {
+        #[arg(short, long)]
+        job_id: String,
+    },
+    Dispute {
+        #[arg(short, long)]
+        job_id: String,
+    },
+    Resolve {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        refund_percent: f64,
+    },
+    ClaimRefund {
+        #[arg(short, long)]
+        job_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum MilestoneCommands {
+    Set {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        stages: String,
+        #[arg(short, long)]
+        approver: Option<String>,
+    },
+    Release {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        index: u8,
+    },
+}
+
+#[derive(Subcommand)]
+enum ItemCommands {
+    Set {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        items: String,
+        #[arg(long, default_value_t = 0.0)]
+        tax_percent: f64,
+        #[arg(long, default_value_t = 0.0)]
+        tip_percent: f64,
+    },
+    Claim {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        index: u8,
+        #[arg(short, long, default_value_t = 100.0)]
+        percent: f64,
+    },
+}
+
+#[derive(Subcommand)]
+enum AssetCommands {
+    Add {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        mint: String,
+        #[arg(long)]
+        per_sol: f64,
+    },
+}
+
+#[derive(Subcommand)]
+enum VestingCommands {
+    Set {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        start: i64,
+        #[arg(short, long)]
+        end: i64,
+    },
+    Claim {
+        #[arg(short, long)]
+        job_id: String,
+    },
+    Preview {
+        #[arg(short, long)]
+        job_id: String,
+        #[arg(short, long)]
+        at: Option<i64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum GroupCommands {
+    Create {
+        #[arg(short, long)]
+        members: String,
+    },
+    Expense {
+        #[arg(short, long)]
+        group_id: String,
+        #[arg(short, long)]
+        amount: f64,
+        #[arg(short, long)]
+        fronted_by: Option<String>,
+        #[arg(short, long)]
+        shared_by: String,
+    },
+    Settle {
+        #[arg(short, long)]
+        group_id: String,
+        #[arg(short, long)]
+        to: String,
+        #[arg(short, long)]
+        amount: Option<f64>,
+    },
+    Show {
+        #[arg(short, long)]
+        group_id: String,
+    },
 }
 
 fn main() {
@@ -51,14 +427,23 @@ fn main() {
         Commands::Version => {
             println!("cli version 0.0.0");
         },
-        Commands::CreateJob { payers, payees, amount, deadline } => {
-            commands::create_job(payers, payees, amount, deadline)?;
+        Commands::CreateJob { payers, payees, amount, deadline, late_fee, late_fee_bps, late_fee_per_day, require_acceptance, allow_withdrawals, every, split_total, mint, price_feed, max_staleness, max_confidence_percent, title, description, category, reference } => {
+            let usd = commands::UsdArgs { price_feed, max_staleness, max_confidence_percent };
+            let metadata = commands::MetadataArgs { title, description, category, reference };
+            commands::create_job(payers, payees, amount, deadline, late_fee, late_fee_bps, late_fee_per_day, require_acceptance, allow_withdrawals, every, split_total, mint, usd, metadata)?;
         },
-        Commands::Pay { job_id } => {
-            commands::pay(job_id)?;
+        Commands::EditJob { job_id, title, description, category, reference } => {
+            let metadata = commands::MetadataArgs { title, description, category, reference };
+            commands::edit_job(job_id, metadata)?;
         },
-        Commands::Status { job_id } => {
-            commands::status(job_id)?;
+        Commands::Pay { job_id, memo, store_memo, asset } => {
+            commands::pay(job_id, memo, store_memo, asset)?;
+        },
+        Commands::Prepay { job_id, periods } => {
+            commands::prepay(job_id, periods)?;
+        },
+        Commands::Status { job_id, tree } => {
+            commands::status(job_id, tree)?;
         },
         Commands::Distribute { job_id } => {
             commands::distribute(job_id)?;
@@ -66,6 +451,144 @@ fn main() {
         Commands::Qr { job_id, output } => {
             commands::qr(job_id, output)?;
         },
+        Commands::ExtendDeadline { job_id, deadline, payer } => {
+            commands::extend_deadline(job_id, deadline, payer)?;
+        },
+        Commands::MarkSettled { job_id, payer, note } => {
+            commands::mark_settled(job_id, payer, note)?;
+        },
+        Commands::Roles { command } => match command {
+            RoleCommands::Grant { job_id, wallet, roles } => {
+                commands::grant_roles(job_id, wallet, roles)?;
+            },
+            RoleCommands::Revoke { job_id, wallet } => {
+                commands::revoke_roles(job_id, wallet)?;
+            },
+            RoleCommands::List { job_id } => {
+                commands::list_roles(job_id)?;
+            },
+        },
+        Commands::Payers { command } => match command {
+            PayerCommands::Add { job_id, wallet } => {
+                commands::add_payer(job_id, wallet)?;
+            },
+            PayerCommands::Remove { job_id, wallet } => {
+                commands::remove_payer(job_id, wallet)?;
+            },
+        },
+        Commands::Hook { command } => match command {
+            HookCommands::Set { job_id, program, accounts } => {
+                commands::set_hook(job_id, program, accounts)?;
+            },
+            HookCommands::Clear { job_id } => {
+                commands::clear_hook(job_id)?;
+            },
+        },
+        Commands::Accept { job_id } => {
+            commands::accept(job_id)?;
+        },
+        Commands::Decline { job_id } => {
+            commands::decline(job_id)?;
+        },
+        Commands::Withdraw { job_id } => {
+            commands::withdraw(job_id)?;
+        },
+        Commands::Group { command } => match command {
+            GroupCommands::Create { members } => {
+                commands::create_group(members)?;
+            },
+            GroupCommands::Expense { group_id, amount, fronted_by, shared_by } => {
+                commands::record_expense(group_id, amount, fronted_by, shared_by)?;
+            },
+            GroupCommands::Settle { group_id, to, amount } => {
+                commands::settle(group_id, to, amount)?;
+            },
+            GroupCommands::Show { group_id } => {
+                commands::show_group(group_id)?;
+            },
+        },
+        Commands::Escrow { command } => match command {
+            EscrowCommands::Enable { job_id, arbiter, confirmer } => {
+                commands::enable_escrow(job_id, arbiter, confirmer)?;
+            },
+            EscrowCommands::Confirm { job_id } => {
+                commands::confirm_delivery(job_id)?;
+            },
+            EscrowCommands::Dispute { job_id } => {
+                commands::dispute(job_id)?;
+            },
+            EscrowCommands::Resolve { job_id, refund_percent } => {
+                commands::resolve_dispute(job_id, refund_percent)?;
+            },
+            EscrowCommands::ClaimRefund { job_id } => {
+                commands::claim_refund(job_id)?;
+            },
+        },
+        Commands::Milestones { command } => match command {
+            MilestoneCommands::Set { job_id, stages, approver } => {
+                commands::set_milestones(job_id, stages, approver)?;
+            },
+            MilestoneCommands::Release { job_id, index } => {
+                commands::release_milestone(job_id, index)?;
+            },
+        },
+        Commands::Vesting { command } => match command {
+            VestingCommands::Set { job_id, start, end } => {
+                commands::set_vesting(job_id, start, end)?;
+            },
+            VestingCommands::Claim { job_id } => {
+                commands::claim_vested(job_id)?;
+            },
+            VestingCommands::Preview { job_id, at } => {
+                commands::preview_vesting(job_id, at)?;
+            },
+        },
+        Commands::Items { command } => match command {
+            ItemCommands::Set { job_id, items, tax_percent, tip_percent } => {
+                commands::set_items(job_id, items, tax_percent, tip_percent)?;
+            },
+            ItemCommands::Claim { job_id, index, percent } => {
+                commands::claim_item(job_id, index, percent)?;
+            },
+        },
+        Commands::Assets { command } => match command {
+            AssetCommands::Add { job_id, mint, per_sol } => {
+                commands::add_asset(job_id, mint, per_sol)?;
+            },
+        },
+        Commands::AddChild { job_id, child } => {
+            commands::add_child(job_id, child)?;
+        },
+        Commands::Sponsor { job_id, ratio_percent, cap, deposit, sponsor_keypair } => {
+            commands::add_sponsor(job_id, ratio_percent, cap, deposit, sponsor_keypair)?;
+        },
+        Commands::SetCrankBounty { job_id, bounty } => {
+            commands::set_crank_bounty(job_id, bounty)?;
+        },
+        Commands::Crank { all } => {
+            commands::crank(all)?;
+        },
+        Commands::MockPrice { price, confidence, publish_time, output, address } => {
+            commands::mock_price(price, confidence, publish_time, output, address)?;
+        },
+        Commands::CreateProfile => {
+            commands::create_profile()?;
+        },
+        Commands::Profile { wallet } => {
+            commands::show_profile(wallet)?;
+        },
+        Commands::ReportLate { job_id } => {
+            commands::report_late(job_id)?;
+        },
+        Commands::SetApprovers { job_id, approvers, threshold } => {
+            commands::set_approvers(job_id, approvers, threshold)?;
+        },
+        Commands::Approve { job_id } => {
+            commands::approve(job_id)?;
+        },
+        Commands::Net { job_ids, execute, signers } => {
+            commands::net_jobs(job_ids, execute, signers)?;
+        },
     }
 
     Ok(())
@@ -0,0 +1,203 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{self, get_instruction_relative};
+
+declare_id!("67La57jojWLFKY8W6m8i8nM1uFfJZBEj94eNE7foKZdh");
+
+// tab_pool, the only program allowed to report completions
+pub const TAB_POOL_ID: Pubkey = pubkey!("Hvddzj6Z3eFJheuabJGQNuchCoo4LjwDAQizRVTqW31D");
+
+/**
+ * Sample receiver of tab_pool completion hooks, recording each job's completions in a
+ * receipt. Register it on a job with the receipt (writable) and the instructions sysvar
+ * as hook accounts, in that order.
+ */
+#[program]
+mod tab_hook_receiver {
+    use super::*;
+
+    /**
+     * Creates the receipt a job's hook writes to. Must exist before the hook fires,
+     * since hooks are invoked without a signer to pay for it.
+     */
+    pub fn create_receipt(ctx: Context<CreateReceipt>, job: Pubkey) -> Result<()> {
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.job = job;
+        receipt.funded = false;
+        receipt.distributions = 0;
+        receipt.last_collected = 0;
+
+        msg!("Receipt created for job {}", job);
+        Ok(())
+    }
+
+    /**
+     * Completion hook invoked by tab_pool. Anyone could call this directly, so it only
+     * accepts calls made from within a tab_pool instruction on a tab_pool job.
+     */
+    pub fn on_tab_completed(ctx: Context<OnTabCompleted>, job: Pubkey, event: CompletionEvent, collected: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.job.key(), job, ReceiverError::WrongJob);
+        require_keys_eq!(*ctx.accounts.job.owner, TAB_POOL_ID, ReceiverError::NotTabPool);
+        let caller = get_instruction_relative(0, &ctx.accounts.instructions)?;
+        require_keys_eq!(caller.program_id, TAB_POOL_ID, ReceiverError::NotTabPool);
+
+        let receipt = &mut ctx.accounts.receipt;
+        match event {
+            CompletionEvent::Funded => receipt.funded = true,
+            CompletionEvent::Distributed => receipt.distributions += 1,
+        }
+        receipt.last_collected = collected;
+
+        msg!("Job {} completed: {:?}, {} collected", job, event, collected);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(job: Pubkey)]
+pub struct CreateReceipt<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Receipt::SIZE,
+        seeds = [b"receipt", job.as_ref()],
+        bump,
+    )]
+    pub receipt: Account<'info, Receipt>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OnTabCompleted<'info> {
+    /// CHECK: the completed tab_pool job, checked against its owner
+    pub job: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"receipt", job.key().as_ref()], bump)]
+    pub receipt: Account<'info, Receipt>,
+
+    /// CHECK: the instructions sysvar, checked by its address
+    #[account(address = instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[account]
+pub struct Receipt {
+    pub job: Pubkey, // Job this receipt tracks
+    pub funded: bool, // Whether every payer paid or settled
+    pub distributions: u32, // Times the job was distributed (once per period for recurring jobs)
+    pub last_collected: u64, // Amount collected as of the last completion
+}
+
+impl Receipt {
+    pub const SIZE: usize = 32 + 1 + 4 + 8;
+}
+
+// Mirrors tab_pool's `CompletionEvent`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompletionEvent {
+    Funded,
+    Distributed,
+}
+
+#[error_code]
+pub enum ReceiverError {
+    #[msg("Job account does not match the job argument")]
+    WrongJob,
+    #[msg("Completions must come from tab_pool")]
+    NotTabPool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::hash::hash;
+    use anchor_lang::solana_program::sysvar::instructions::{construct_instructions_data, BorrowedAccountMeta, BorrowedInstruction};
+    use anchor_lang::{Discriminator, InstructionData};
+
+    // What tab_pool sends: its discriminator constant, then job, event and collected
+    const TAB_POOL_DISCRIMINATOR: [u8; 8] = [76, 46, 231, 229, 66, 143, 137, 53];
+
+    fn account(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> &'static AccountInfo<'static> {
+        Box::leak(Box::new(AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            true,
+            Box::leak(Box::new(1)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        )))
+    }
+
+    // Instructions sysvar for a transaction whose only instruction calls `program_id`
+    fn instructions(program_id: Pubkey) -> &'static AccountInfo<'static> {
+        let job = Pubkey::new_unique();
+        let data = construct_instructions_data(&[BorrowedInstruction {
+            program_id: &program_id,
+            accounts: vec![BorrowedAccountMeta { pubkey: &job, is_signer: false, is_writable: true }],
+            data: &[],
+        }]);
+        account(instructions::ID, Pubkey::default(), data)
+    }
+
+    fn receipt(job: Pubkey) -> Account<'static, Receipt> {
+        let mut data = Receipt::DISCRIMINATOR.to_vec();
+        Receipt { job, funded: false, distributions: 0, last_collected: 0 }.serialize(&mut data).unwrap();
+        Account::try_from(account(Pubkey::new_unique(), crate::ID, data)).unwrap()
+    }
+
+    fn complete(job: &'static AccountInfo<'static>, caller: Pubkey, job_arg: Pubkey) -> Result<Account<'static, Receipt>> {
+        let mut accounts = OnTabCompleted {
+            job: UncheckedAccount::try_from(job),
+            receipt: receipt(*job.key),
+            instructions: UncheckedAccount::try_from(instructions(caller)),
+        };
+        crate::tab_hook_receiver::on_tab_completed(
+            Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+            job_arg,
+            CompletionEvent::Distributed,
+            1_500,
+        )?;
+        Ok(accounts.receipt)
+    }
+
+    fn assert_error<T>(result: Result<T>, code: ReceiverError) {
+        match result {
+            Err(Error::AnchorError(e)) => assert_eq!(e.error_code_number, u32::from(code)),
+            Err(e) => panic!("unexpected error {e:?}"),
+            Ok(_) => panic!("expected {code:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_completions_only_from_tab_pool() {
+        let job = account(Pubkey::new_unique(), TAB_POOL_ID, Vec::new());
+        let receipt = complete(job, TAB_POOL_ID, *job.key).unwrap();
+        assert_eq!((receipt.distributions, receipt.last_collected), (1, 1_500));
+
+        assert_error(complete(job, TAB_POOL_ID, Pubkey::new_unique()), ReceiverError::WrongJob);
+        assert_error(complete(job, Pubkey::new_unique(), *job.key), ReceiverError::NotTabPool);
+        let forged = account(Pubkey::new_unique(), Pubkey::new_unique(), Vec::new());
+        assert_error(complete(forged, TAB_POOL_ID, *forged.key), ReceiverError::NotTabPool);
+    }
+
+    #[test]
+    fn decodes_the_payload_tab_pool_sends() {
+        assert_eq!(TAB_POOL_DISCRIMINATOR, hash(b"global:on_tab_completed").to_bytes()[..8]);
+        assert_eq!(instruction::OnTabCompleted::DISCRIMINATOR, TAB_POOL_DISCRIMINATOR);
+
+        let job = Pubkey::new_unique();
+        let mut data = TAB_POOL_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(job.as_ref());
+        data.push(1);
+        data.extend_from_slice(&1_500u64.to_le_bytes());
+
+        let args = instruction::OnTabCompleted::try_from_slice(&data[8..]).unwrap();
+        assert_eq!((args.job, args.event, args.collected), (job, CompletionEvent::Distributed, 1_500));
+        assert_eq!(instruction::OnTabCompleted { job, event: CompletionEvent::Distributed, collected: 1_500 }.data(), data);
+    }
+}
@@ -1,5 +1,16 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{program::invoke, system_instruction};
+use anchor_lang::solana_program::{instruction::{AccountMeta, Instruction}, program::invoke, system_instruction};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+};
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+use tab_shared::fees::{self, FeeBasis};
+use tab_shared::itemized;
+use tab_shared::netting::{self, Obligation, Transfer};
+use tab_shared::oracle::{self, PriceError, PriceFeed};
+use tab_shared::pricing;
+use tab_shared::vesting;
 
 declare_id!("Hvddzj6Z3eFJheuabJGQNuchCoo4LjwDAQizRVTqW31D");
 
@@ -8,31 +19,113 @@ mod tab_pool {
     use super::*;
 
     /**
-     * 
+     * When `split_total` is set, `amount` is the total bill: it is split evenly across
+     * payers and the remainder lamports are owed by the first payers listed, one each.
+     * Passing a mint and its token program (legacy or Token-2022) creates a token job
+     * whose amounts are in the mint's base units and whose funds are held in a vault.
+     * Passing `usd` and a Pyth-format price feed creates a job whose amounts are in USD
+     * cents, converted to lamports at the feed's price when each payer pays.
      */
-    pub fn create_payment_job(ctx: Context<CreatePaymentJob>, payers: Vec<Pubkey>,payees: Vec<Pubkey>,amount: u64,deadline: Option<i64>) -> Result<()> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_payment_job(ctx: Context<CreatePaymentJob>, payers: Vec<Pubkey>,payees: Vec<Pubkey>,amount: u64,deadline: Option<i64>,late_fee: Option<LateFee>,require_acceptance: bool,allow_withdrawals: bool,period: Option<i64>,split_total: bool,usd: Option<UsdConfig>) -> Result<()> {
         require!(payers.len() > 0, ErrorCode::InvalidInput);
         require!(payees.len() > 0, ErrorCode::InvalidInput);
+        let (amount, remainder) = if split_total {
+            (amount / payers.len() as u64, amount % payers.len() as u64)
+        } else {
+            (amount, 0)
+        };
         require!(amount > 0, ErrorCode::InvalidInput);
+        if let Some(fee) = late_fee {
+            require!(fee.value > 0, ErrorCode::InvalidInput);
+            require!(deadline.is_some(), ErrorCode::InvalidInput);
+        }
+        if let Some(period) = period {
+            // Each period's deadline is derived from the first one
+            require!(period > 0, ErrorCode::InvalidInput);
+            require!(deadline.is_some(), ErrorCode::InvalidInput);
+            require!(payers.len() <= MAX_RECURRING_PAYERS, ErrorCode::InvalidInput);
+        }
 
         let job = &mut ctx.accounts.job;
         job.authority = ctx.accounts.authority.key();
         job.amount = amount;
+        job.remainder = remainder as u16;
+        job.split_total = split_total;
         job.deadline = deadline.unwrap_or(i64::MAX);
         job.closed = false;
+        job.late_fee = late_fee;
+        job.late_fees_collected = 0;
+        job.require_acceptance = require_acceptance;
+        job.allow_withdrawals = allow_withdrawals;
+        job.recurrence = period.map(|period| Recurrence { period, current_period: 0, history: Vec::new() });
+        job.escrow = None;
+        job.milestones = None;
+        job.approvals = None;
+        job.vesting = None;
+        job.sponsorship = None;
+        job.itemization = None;
+        job.crank_bounty = 0;
+        job.accepted_assets = Vec::new();
+        job.parent = None;
+        job.children = Vec::new();
+        job.operators = Vec::new();
+        job.completion_hook = None;
+
+        // USD jobs check the feed is usable up front
+        job.usd_pricing = match (usd, &ctx.accounts.price_feed) {
+            (Some(usd), Some(price_feed)) => {
+                require!(period.is_none() && ctx.accounts.mint.is_none(), ErrorCode::UnsupportedForUsd);
+                require!(usd.max_staleness > 0, ErrorCode::InvalidInput);
+                require!(usd.max_confidence_bps > 0 && usd.max_confidence_bps <= 10_000, ErrorCode::InvalidInput);
+                let pricing = UsdPricing {
+                    price_feed: price_feed.key(),
+                    max_staleness: usd.max_staleness,
+                    max_confidence_bps: usd.max_confidence_bps,
+                    collected: 0,
+                };
+                pricing.current_price(&price_feed.to_account_info(), Clock::get()?.unix_timestamp)?;
+                Some(pricing)
+            }
+            (None, None) => None,
+            _ => return err!(ErrorCode::MissingAccount),
+        };
+
+        // Token jobs hold their funds in a vault owned by the vault PDA itself
+        job.token = match (&ctx.accounts.mint, &ctx.accounts.token_program) {
+            (Some(mint), Some(token_program)) => {
+                check_mint_extensions(&mint.to_account_info())?;
+                Some(TokenConfig {
+                    mint: mint.key(),
+                    token_program: token_program.key(),
+                    vault_bump: ctx.bumps.vault.ok_or(error!(ErrorCode::MissingAccount))?,
+                })
+            }
+            (None, None) => None,
+            _ => return err!(ErrorCode::MissingAccount),
+        };
         job.payees = payees;
-        job.payers = payers.into_iter().map(|pk| WalletStatus { wallet: pk, paid: false }).collect();
+        job.payers = payers.into_iter().map(|pk| WalletStatus { wallet: pk, paid: false, deadline: None, settled_off_chain: false, settlement_note: None, acceptance: Acceptance::Pending, prepaid_periods: 0, matched: 0, memo: None, late_recorded: false, paid_late: false, paid_in: None }).collect();
 
         msg!("Payment job created with {} payers to {} as payee", job.payers.len(), job.payees.len());
         Ok(())
     }
 
     /**
-    * 
+    * An optional memo is emitted with the payment, and kept in the payer's record
+    * when `store_memo` is set. Passing the payer's profile records the payment on it,
+    * except on jobs that allow withdrawals where the payment may still be taken back.
+    * The payment that resolves the last payer fires the job's completion hook, whose
+    * accounts are passed as remaining accounts.
     */
-    pub fn pay(ctx: Context<Pay>) -> Result<()> {
+    pub fn pay<'info>(ctx: Context<'_, '_, 'info, 'info, Pay<'info>>, memo: Option<String>, store_memo: bool) -> Result<()> {
+        if let Some(memo) = &memo {
+            require!(memo.len() <= MAX_MEMO_LEN, ErrorCode::MemoTooLong);
+        }
+
         // Check if job is closed first
         require!(!ctx.accounts.job.closed, ErrorCode::AlreadyClosed);
+        require!(ctx.accounts.job.payments_open(), ErrorCode::PaymentsNotOpen);
 
         // Store payer key
         let payer_key = ctx.accounts.payer.key();
@@ -41,7 +134,8 @@ mod tab_pool {
         let mut payer_index = None;
         for (i, ws) in ctx.accounts.job.payers.iter().enumerate() {
             if ws.wallet == payer_key {
-                require!(!ws.paid, ErrorCode::AlreadyPaid);
+                require!(!ws.is_resolved(), ErrorCode::AlreadyPaid);
+                require!(ws.acceptance != Acceptance::Declined, ErrorCode::Declined);
                 payer_index = Some(i);
                 break;
             }
@@ -50,62 +144,274 @@ mod tab_pool {
         // Check if payer was found
         let payer_index = payer_index.ok_or(error!(ErrorCode::NotContributor))?;
 
-        // Get key and amount before transfer
+        // Get key and amount (including any late fee) before transfer
         let job_key = ctx.accounts.job.key();
-        let amount = ctx.accounts.job.amount;
+        let clock = Clock::get()?;
+        let owed = ctx.accounts.job.amount_owed(payer_index)?;
+        require!(owed > 0, ErrorCode::NothingClaimed);
+        let late_fee = ctx.accounts.job.late_fee_for(payer_index, clock.unix_timestamp)?;
 
-        // Transfer SOL from payer to job account
-        invoke(
-            &system_instruction::transfer(
-                &payer_key,
-                &job_key,
-                amount,
-            ),
-            &[
-                ctx.accounts.payer.to_account_info(),
-                ctx.accounts.job.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-        )?;
+        // USD jobs owe cents, converted to lamports at the feed's current price
+        let (owed, late_fee) = match &ctx.accounts.job.usd_pricing {
+            Some(usd) => {
+                let price_feed = ctx.accounts.price_feed.as_ref().ok_or(error!(ErrorCode::MissingAccount))?;
+                require!(price_feed.key() == usd.price_feed, ErrorCode::WrongPriceFeed);
+                let price = usd.current_price(&price_feed.to_account_info(), clock.unix_timestamp)?;
+                (
+                    price.cents_to_lamports(owed).ok_or(error!(ErrorCode::MathOverflow))?,
+                    price.cents_to_lamports(late_fee).ok_or(error!(ErrorCode::MathOverflow))?,
+                )
+            }
+            None => (owed, late_fee),
+        };
+        let amount = owed
+            .checked_add(late_fee)
+            .ok_or(error!(ErrorCode::MathOverflow))?;
+
+        if let Some(token) = ctx.accounts.job.token {
+            // Transfer tokens from payer to the vault, grossed up so the vault receives
+            // the full amount after any transfer fee
+            let from = ctx.accounts.payer_token_account.as_ref().ok_or(error!(ErrorCode::MissingAccount))?;
+            let vault = ctx.accounts.vault.as_ref().ok_or(error!(ErrorCode::MissingAccount))?;
+            let (mint, token_program) = token_accounts(&token, &ctx.accounts.mint, &ctx.accounts.token_program)?;
+            let gross = with_transfer_fee(&mint.to_account_info(), amount)?;
+
+            transfer_checked(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from: from.to_account_info(),
+                        mint: mint.to_account_info(),
+                        to: vault.to_account_info(),
+                        authority: ctx.accounts.payer.to_account_info(),
+                    },
+                ),
+                gross,
+                mint.decimals,
+            )?;
+        } else {
+            // Transfer SOL from payer to job account
+            invoke(
+                &system_instruction::transfer(
+                    &payer_key,
+                    &job_key,
+                    amount,
+                ),
+                &[
+                    ctx.accounts.payer.to_account_info(),
+                    ctx.accounts.job.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
 
         // Now get mutable reference and mark payer as paid
-        ctx.accounts.job.payers[payer_index].paid = true;
-        msg!("{} paid {} lamports", payer_key, amount);
+        let job = &mut ctx.accounts.job;
+        job.payers[payer_index].paid = true;
+        job.payers[payer_index].acceptance = Acceptance::Accepted;
+        if store_memo {
+            job.payers[payer_index].memo = memo.clone();
+        }
+
+        // Track the payment on the payer's reputation profile (totals count lamports only)
+        let late = clock.unix_timestamp > job.payers[payer_index].effective_deadline(job.deadline);
+        job.payers[payer_index].paid_late = late;
+        if let Some(profile) = ctx.accounts.profile.as_mut() {
+            require!(!job.allow_withdrawals, ErrorCode::WithdrawalsAllowed);
+            let lamports = if job.token.is_some() { 0 } else { amount };
+            let ws = &mut job.payers[payer_index];
+            profile.record_payment(lamports, late, ws.late_recorded)?;
+            ws.late_recorded |= late;
+        }
+        job.late_fees_collected = job.late_fees_collected
+            .checked_add(late_fee)
+            .ok_or(error!(ErrorCode::MathOverflow))?;
+        if let Some(itemization) = job.itemization.as_mut() {
+            itemization.collected = itemization.collected
+                .checked_add(owed)
+                .ok_or(error!(ErrorCode::MathOverflow))?;
+        }
+        if let Some(usd) = job.usd_pricing.as_mut() {
+            usd.collected = usd.collected
+                .checked_add(owed)
+                .ok_or(error!(ErrorCode::MathOverflow))?;
+        }
+        if late_fee > 0 {
+            msg!("{} paid {} lamports including a {} lamport late fee", payer_key, amount, late_fee);
+        } else {
+            msg!("{} paid {} lamports", payer_key, amount);
+        }
+        emit!(PaymentMade {
+            job: job_key,
+            payer: payer_key,
+            amount,
+            memo,
+        });
 
-        // Check if all contributors have paid - if yes, auto-distribute
-        if ctx.accounts.job.payers.iter().all(|c| c.paid) {
+        // Match the contribution from the sponsor's pool
+        let job_amount = job.amount;
+        if let Some(sponsorship) = job.sponsorship.as_mut() {
+            let matched = sponsorship.match_for(job_amount);
+            sponsorship.pool -= matched;
+            sponsorship.matched += matched;
+            job.payers[payer_index].matched = matched;
+            if matched > 0 {
+                msg!("Sponsor matched {} lamports", matched);
+            }
+        }
+
+        // Check if all contributors have paid or settled - if yes, auto-distribute
+        if ctx.accounts.job.payers.iter().all(|c| c.is_resolved()) {
             msg!("All contributors have paid. Auto-distributing funds.");
             //return _distribute_funds(job, &ctx.accounts.system_program);
+            let collected = ctx.accounts.job.collected();
+            fire_completion_hook(&mut ctx.accounts.job, &ctx.accounts.hook_program, ctx.remaining_accounts, CompletionEvent::Funded, collected)?;
         }
 
         Ok(())
     }
 
-    pub fn distribute_funds(ctx: Context<DistributeFunds>) -> Result<()> {
+    /**
+     * Token jobs pay out of the vault into the payees' token accounts, passed as
+     * remaining accounts in `payees` order. Distributing fires the job's completion
+     * hook, whose accounts follow any payee token accounts, unless an unprivileged
+     * caller skips it after `HOOK_GRACE_PERIOD`.
+     */
+    pub fn distribute_funds<'info>(ctx: Context<'_, '_, 'info, 'info, DistributeFunds<'info>>) -> Result<()> {
         let job = &mut ctx.accounts.job;
         require!(!job.closed, ErrorCode::AlreadyClosed);
 
-        // If authority is not the signer, check deadline
-        if !ctx.accounts.authority.key().eq(&job.authority) || !ctx.accounts.authority.is_signer {
-            let clock = Clock::get()?;
-            require!(clock.unix_timestamp >= job.deadline, ErrorCode::BeforeDeadline);
+        // Unless the authority or a distributing operator signs, check deadline (including
+        // extra time given to unpaid payers)
+        let caller = ctx.accounts.authority.key();
+        let privileged = ctx.accounts.authority.is_signer && job.is_authorized(&caller, ROLE_DISTRIBUTE);
+        let now = Clock::get()?.unix_timestamp;
+        let early = now < job.distribution_deadline();
+        require!(privileged || !early, ErrorCode::BeforeDeadline);
+
+        // A failing hook can't hold up the crank forever: once the grace period has
+        // passed, anyone distributing may leave the hook program out to skip it
+        let hook_expired = now >= job.distribution_deadline().saturating_add(HOOK_GRACE_PERIOD);
+        let skip_hook = !privileged && hook_expired && ctx.accounts.hook_program.is_none();
+
+        // Operators distributing early can't collect the authority's crank bounty
+        require!(!early || caller == job.authority || job.crank_bounty == 0, ErrorCode::Unauthorized);
+
+        // Staged and vesting jobs pay out through their own instructions
+        require!(job.milestones.is_none(), ErrorCode::UseMilestones);
+        require!(job.vesting.is_none(), ErrorCode::UseVesting);
+
+        // Jobs with an approver set need enough approvals first
+        require!(job.distribution_approved(), ErrorCode::NotApproved);
+
+        // Escrow jobs release funds only once delivery is confirmed or a dispute is resolved
+        if let Some(escrow) = &job.escrow {
+            require!(escrow.is_released(), ErrorCode::EscrowNotReleased);
+        }
+
+        // Parent jobs pay out once every child job has been distributed into them
+        require!(job.children.iter().all(|c| c.settled), ErrorCode::ChildrenPending);
+
+        // Child jobs credit their parent as they close
+        let job_key = job.key();
+        if let Some(parent_key) = job.parent {
+            let parent = ctx.accounts.parent.as_ref().ok_or(error!(ErrorCode::MissingAccount))?;
+            require!(parent.key() == parent_key, ErrorCode::MissingAccount);
+        }
+
+        // The crank bounty goes to whoever distributes; the authority distributing
+        // early simply gets their deposit back
+        let bounty = std::mem::take(&mut job.crank_bounty);
+        if bounty > 0 {
+            let caller = &ctx.accounts.authority;
+            require!(caller.is_signer, ErrorCode::MissingSigner);
+            pay_from_job(job, caller, bounty)?;
+            msg!("Paid {} lamport crank bounty to {}", bounty, caller.key());
+        }
+
+        // Return what is left of the sponsor's matching pool
+        if let Some(sponsorship) = job.sponsorship.as_mut() {
+            let sponsor = ctx.accounts.sponsor.as_ref().ok_or(error!(ErrorCode::MissingAccount))?;
+            require!(sponsor.key() == sponsorship.sponsor, ErrorCode::MissingAccount);
+
+            let leftover = sponsorship.pool;
+            sponsorship.pool = 0;
+            if leftover > 0 {
+                pay_from_job(job, sponsor, leftover)?;
+                msg!("Returned {} unused lamports to sponsor {}", leftover, sponsor.key());
+            }
         }
 
-        // Count paid contributors
+        // Count paid contributors (off-chain settlements never reached the job account)
         let paid_count = job.payers.iter().filter(|p| p.paid).count();
-        if paid_count == 0 {
+        if paid_count == 0 && job.children_credited() == 0 {
+            job.closed = true;
+            if !skip_hook {
+                fire_completion_hook(job, &ctx.accounts.hook_program, ctx.remaining_accounts, CompletionEvent::Distributed, 0)?;
+            } else if job.completion_hook.is_some() {
+                msg!("Skipped the completion hook");
+            }
+            job.roll_over(0, now)?;
+            if let Some(parent) = ctx.accounts.parent.as_mut() {
+                parent.credit_child(job_key, 0)?;
+            }
+            return Ok(());
+        }
+
+        if let Some(token) = job.token {
+            let vault = ctx.accounts.vault.as_ref().ok_or(error!(ErrorCode::MissingAccount))?;
+            let (mint, token_program) = token_accounts(&token, &ctx.accounts.mint, &ctx.accounts.token_program)?;
+            let total_collected = job.collected();
+            require!(vault.amount >= total_collected, ErrorCode::InsufficientFunds);
+            let hook_accounts = job.completion_hook.as_ref().filter(|_| !skip_hook).map_or(0, |h| h.accounts.len());
+            require!(ctx.remaining_accounts.len() == job.payees.len() + hook_accounts, ErrorCode::MissingAccount);
+
+            let job_key = job.key();
+            let seeds: &[&[u8]] = &[b"vault", job_key.as_ref(), &[token.vault_bump]];
             job.closed = true;
+
+            let shares = netting::split_evenly(total_collected, job.payees.len());
+            for ((payee, payee_account), share) in job.payees.iter().zip(ctx.remaining_accounts.iter()).zip(shares) {
+                let destination = InterfaceAccount::<TokenAccount>::try_from(payee_account)?;
+                require!(destination.owner == *payee && destination.mint == token.mint, ErrorCode::WrongMint);
+
+                transfer_checked(
+                    CpiContext::new_with_signer(
+                        token_program.to_account_info(),
+                        TransferChecked {
+                            from: vault.to_account_info(),
+                            mint: mint.to_account_info(),
+                            to: payee_account.clone(),
+                            authority: vault.to_account_info(),
+                        },
+                        &[seeds],
+                    ),
+                    share,
+                    mint.decimals,
+                )?;
+                msg!("Transferred {} tokens to payee {}", share, payee);
+            }
+
+            if !skip_hook {
+                fire_completion_hook(job, &ctx.accounts.hook_program, ctx.remaining_accounts, CompletionEvent::Distributed, total_collected)?;
+            } else if job.completion_hook.is_some() {
+                msg!("Skipped the completion hook");
+            }
+            job.roll_over(total_collected, now)?;
             return Ok(());
         }
 
-        // Calculate amounts
-        let total_collected = paid_count as u64 * job.amount;
+        // Calculate amounts (late fees go to payees along with the contributions)
+        let total_collected = job.collected();
         let current_job_lamports = job.to_account_info().lamports();
         require!(current_job_lamports >= total_collected, ErrorCode::InsufficientFunds);
 
-        let distributable_amount = std::cmp::min(total_collected, current_job_lamports);
+        // Refunds granted by the arbiter stay in the job until payers claim them
+        let refunds = job.escrow_refunds()?;
+        let distributable_amount = std::cmp::min(total_collected, current_job_lamports) - refunds;
         let per_payee = distributable_amount / job.payees.len() as u64;
 
+        let mut credited = 0;
         if per_payee > 0 {
             // Clone payees to avoid borrow checker issues
             let payees = job.payees.clone();
@@ -115,6 +421,15 @@ mod tab_pool {
 
             // Process transfers
             for payee in payees.iter() {
+                // A parent job is paid directly, as a payment into it
+                if job.parent == Some(*payee) {
+                    let parent = ctx.accounts.parent.as_mut().ok_or(error!(ErrorCode::MissingAccount))?;
+                    pay_from_job(job, parent, per_payee)?;
+                    credited += per_payee;
+                    msg!("Credited {} lamports to parent job {}", per_payee, payee);
+                    continue;
+                }
+
                 // Create the instruction
                 let ix = system_instruction::transfer(
                     &job.key(),
@@ -137,97 +452,3424 @@ mod tab_pool {
             job.closed = true;
             msg!("Payment job closed (no funds to distribute)");
         }
+        if let Some(parent) = ctx.accounts.parent.as_mut() {
+            parent.credit_child(job_key, credited)?;
+        }
+
+        // Let the hook see the closed job before recurring jobs reopen for the next period
+        if !skip_hook {
+            fire_completion_hook(job, &ctx.accounts.hook_program, ctx.remaining_accounts, CompletionEvent::Distributed, total_collected)?;
+        } else if job.completion_hook.is_some() {
+            msg!("Skipped the completion hook");
+        }
+        job.roll_over(total_collected, now)?;
         
         Ok(())
     }
-}
 
-// ==================== Account Structs ====================
+    /**
+     * Turns a job into an escrow: collected funds are released to payees only after
+     * the confirmer approves delivery, and the arbiter resolves disputes.
+     * Must be enabled before anyone pays.
+     */
+    pub fn enable_escrow(ctx: Context<EnableEscrow>, confirmer: Confirmer, arbiter: Pubkey) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        require!(job.escrow.is_none(), ErrorCode::InvalidInput);
+        require!(job.recurrence.is_none() && job.milestones.is_none() && job.vesting.is_none(), ErrorCode::InvalidInput);
+        require!(job.itemization.is_none(), ErrorCode::InvalidInput);
+        require!(job.token.is_none() && job.accepted_assets.is_empty(), ErrorCode::UnsupportedForTokens);
+        require!(job.usd_pricing.is_none(), ErrorCode::UnsupportedForUsd);
+        require!(job.payers.len() <= MAX_ESCROW_PAYERS, ErrorCode::InvalidInput);
+        require!(job.payers.iter().all(|p| !p.paid), ErrorCode::AlreadyPaid);
 
-#[derive(Accounts)]
-#[instruction(
-    payers: Vec<Pubkey>,
-    payee: Vec<Pubkey>,
-    amount: u64,
-    deadline: Option<u64>,
-)]
-pub struct CreatePaymentJob<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = PaymentJob::space(payers.len(), payee.len()),
-    )]
-    pub job: Account<'info, PaymentJob>,
+        job.escrow = Some(Escrow {
+            confirmer,
+            arbiter,
+            state: EscrowState::Pending,
+            confirmations: 0,
+            refund_bps: 0,
+            refunded: 0,
+        });
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+        msg!("Escrow enabled with arbiter {}", arbiter);
+        Ok(())
+    }
 
-    pub system_program: Program<'info, System>,
-}
+    /**
+     * Confirms the payees delivered. In majority mode each paid payer confirms and funds
+     * release once more than half of the contributing payers have, counting those who
+     * paid or still owe but not those who declined or settled off-chain; in arbiter mode
+     * the arbiter confirms alone.
+     */
+    pub fn confirm_delivery(ctx: Context<EscrowAction>) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+        let signer = ctx.accounts.signer.key();
+        let num_payers = job.contributing_payers() as u32;
+        let payer_index = job.payers.iter().position(|p| p.wallet == signer && p.paid);
 
-#[derive(Accounts)]
-pub struct Pay<'info> {
-    #[account(mut)]
-    pub job: Account<'info, PaymentJob>,
-    
-    #[account(mut)]
-    pub payer: Signer<'info>,
+        let escrow = job.escrow.as_mut().ok_or(error!(ErrorCode::NotEscrow))?;
+        require!(escrow.state == EscrowState::Pending, ErrorCode::InvalidEscrowState);
 
-    pub system_program: Program<'info, System>,
-}
+        match escrow.confirmer {
+            Confirmer::Arbiter => {
+                require!(signer == escrow.arbiter, ErrorCode::Unauthorized);
+                escrow.state = EscrowState::Confirmed;
+            }
+            Confirmer::PayerMajority => {
+                let payer_index = payer_index.ok_or(error!(ErrorCode::NotPaid))?;
+                escrow.confirmations |= 1 << payer_index;
+                if escrow.confirmations.count_ones() * 2 > num_payers {
+                    escrow.state = EscrowState::Confirmed;
+                }
+            }
+        }
 
-#[derive(Accounts)]
-pub struct DistributeFunds<'info> {
-    #[account(mut)]
-    pub job: Account<'info, PaymentJob>,
+        msg!("{} confirmed delivery", signer);
+        if escrow.state == EscrowState::Confirmed {
+            msg!("Delivery confirmed, funds can be released");
+        }
+        Ok(())
+    }
 
-    pub authority: UncheckedAccount<'info>,
+    /**
+     * Lets a paid payer dispute delivery, freezing the funds until the arbiter resolves it.
+     */
+    pub fn dispute(ctx: Context<EscrowAction>) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        let signer = ctx.accounts.signer.key();
+        require!(job.payers.iter().any(|p| p.wallet == signer && p.paid), ErrorCode::NotPaid);
 
-    pub system_program: Program<'info, System>,
-}
+        let escrow = job.escrow.as_mut().ok_or(error!(ErrorCode::NotEscrow))?;
+        require!(escrow.state == EscrowState::Pending, ErrorCode::InvalidEscrowState);
+        escrow.state = EscrowState::Disputed;
 
-// ==================== Account Structs ====================
+        msg!("{} disputed delivery", signer);
+        Ok(())
+    }
 
-#[account]
-pub struct PaymentJob {
-    pub authority: Pubkey, // Creator/Payee who controls the job
-    pub amount: u64, // Amount payer owes
-    pub deadline: i64, // Deadline for payment
-    pub closed: bool, // Job closed
-    pub payers: Vec<WalletStatus>, // Payers
-    pub payees: Vec<Pubkey>, // Payee
-}
+    /**
+     * Resolves a dispute. `refund_bps` of every contribution is returned to the paid
+     * payers and the rest is released to payees.
+     */
+    pub fn resolve_dispute(ctx: Context<EscrowAction>, refund_bps: u16) -> Result<()> {
+        require!(refund_bps as u64 <= fees::BPS_DENOMINATOR, ErrorCode::InvalidInput);
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct WalletStatus {
-    pub wallet: Pubkey, // The payer's wallet
-    pub paid: bool, // Whether the payer has paid
-}
+        let job = &mut ctx.accounts.job;
+        let escrow = job.escrow.as_mut().ok_or(error!(ErrorCode::NotEscrow))?;
+        require!(ctx.accounts.signer.key() == escrow.arbiter, ErrorCode::Unauthorized);
+        require!(escrow.state == EscrowState::Disputed, ErrorCode::InvalidEscrowState);
 
-impl PaymentJob {
-    pub fn space(num_payers: usize, num_payees: usize) -> usize {
-        8 +
-        32 +
-        8 +
-        8 +
-        1 +
-        4 + num_payers * std::mem::size_of::<WalletStatus>() +
-        4 + num_payees * 32  
+        escrow.state = EscrowState::Resolved;
+        escrow.refund_bps = refund_bps;
+
+        msg!("Dispute resolved with {} bps refunded to payers", refund_bps);
+        Ok(())
     }
-}
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Invalid input parameters")]
-    InvalidInput,
-    #[msg("Contributor has already paid")]
-    AlreadyPaid,
-    #[msg("Contributor is not listed")]
-    NotContributor,
-    #[msg("Payment job is already closed")]
-    AlreadyClosed,
-    #[msg("Cannot distribute before deadline unless authority")]
-    BeforeDeadline,
-    #[msg("Insufficient funds in job account")]
-    InsufficientFunds,
-}
\ No newline at end of file
+    /**
+     * Pays a paid payer their share of the refund granted by the arbiter, once the
+     * rest of the funds have been distributed.
+     */
+    pub fn claim_refund(ctx: Context<Withdraw>) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+        // Distribution counts on every refund still being in the job
+        require!(job.closed, ErrorCode::NotDistributed);
+        let payer_key = ctx.accounts.payer.key();
+        let payer_index = job.payers.iter()
+            .position(|p| p.wallet == payer_key && p.paid)
+            .ok_or(error!(ErrorCode::NotPaid))?;
+
+        let contribution = job.amount_owed(payer_index)?;
+        let escrow = job.escrow.as_mut().ok_or(error!(ErrorCode::NotEscrow))?;
+        require!(escrow.state == EscrowState::Resolved, ErrorCode::InvalidEscrowState);
+        require!(escrow.refunded & (1 << payer_index) == 0, ErrorCode::AlreadyRefunded);
+
+        let refund = escrow.refund_of(contribution);
+        require!(refund > 0, ErrorCode::InvalidInput);
+        escrow.refunded |= 1 << payer_index;
+
+        pay_from_job(job, &ctx.accounts.payer, refund)?;
+
+        msg!("{} refunded {} lamports", payer_key, refund);
+        Ok(())
+    }
+
+    /**
+     * Splits the payout into milestones released separately by the authority or the
+     * approver. Stage percentages (in basis points) must add up to 100%.
+     */
+    pub fn set_milestones(ctx: Context<SetMilestones>, stages: Vec<MilestoneConfig>, approver: Option<Pubkey>) -> Result<()> {
+        require!(!stages.is_empty() && stages.len() <= MAX_MILESTONES, ErrorCode::InvalidInput);
+        require!(stages.iter().all(|m| m.bps > 0), ErrorCode::InvalidInput);
+        require!(stages.iter().map(|m| m.bps as u64).sum::<u64>() == fees::BPS_DENOMINATOR, ErrorCode::InvalidInput);
+
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        require!(job.milestones.is_none(), ErrorCode::InvalidInput);
+        require!(job.recurrence.is_none() && job.escrow.is_none() && job.vesting.is_none(), ErrorCode::InvalidInput);
+        require!(job.parent.is_none() && job.children.is_empty(), ErrorCode::InvalidInput);
+        require!(job.token.is_none() && job.accepted_assets.is_empty(), ErrorCode::UnsupportedForTokens);
+        require!(job.sponsorship.is_none() && job.crank_bounty == 0, ErrorCode::InvalidInput);
+
+        job.milestones = Some(Milestones {
+            approver,
+            stages: stages.into_iter()
+                .map(|m| Milestone { bps: m.bps, unlock_time: m.unlock_time, released: false })
+                .collect(),
+            total: 0,
+            released: 0,
+        });
+
+        msg!("Payout split into {} milestones", job.milestones.as_ref().map_or(0, |m| m.stages.len()));
+        Ok(())
+    }
+
+    /**
+     * Releases one milestone to the payees. The first release locks the job against
+     * further payments and fixes the total being released, so like distribution it waits
+     * for the deadline unless the signer may distribute early. Remaining accounts are the
+     * payee wallets in `job.payees` order.
+     */
+    pub fn release_milestone<'info>(ctx: Context<'_, '_, 'info, 'info, ReleaseMilestone<'info>>, index: u8) -> Result<()> {
+        let signer = ctx.accounts.signer.key();
+        let clock = Clock::get()?;
+        let job = &mut ctx.accounts.job;
+
+        let collected = job.collected();
+        let was_closed = job.closed;
+        let authority = job.authority;
+        let payees = job.payees.clone();
+        let approved = job.distribution_approved();
+        let early = clock.unix_timestamp < job.distribution_deadline()
+            && !job.is_authorized(&signer, ROLE_DISTRIBUTE);
+
+        let milestones = job.milestones.as_mut().ok_or(error!(ErrorCode::NotStaged))?;
+        require!(signer == authority || Some(signer) == milestones.approver, ErrorCode::Unauthorized);
+
+        // The first release locks in what was collected
+        if milestones.released == 0 && milestones.stages.iter().all(|m| !m.released) {
+            require!(!was_closed, ErrorCode::AlreadyClosed);
+            require!(!early, ErrorCode::BeforeDeadline);
+            require!(approved, ErrorCode::NotApproved);
+            milestones.total = collected;
+        }
+
+        let stage = milestones.stages.get(index as usize).ok_or(error!(ErrorCode::InvalidInput))?;
+        require!(!stage.released, ErrorCode::MilestoneReleased);
+        if let Some(unlock_time) = stage.unlock_time {
+            require!(clock.unix_timestamp >= unlock_time, ErrorCode::MilestoneLocked);
+        }
+
+        // The final stage takes whatever rounding left behind
+        let is_last = milestones.stages.iter().filter(|m| !m.released).count() == 1;
+        let amount = if is_last {
+            milestones.total - milestones.released
+        } else {
+            (milestones.total as u128 * stage.bps as u128 / fees::BPS_DENOMINATOR as u128) as u64
+        };
+
+        milestones.stages[index as usize].released = true;
+        milestones.released += amount;
+        job.closed = true;
+
+        require!(ctx.remaining_accounts.len() == payees.len(), ErrorCode::MissingAccount);
+        for ((payee, payee_info), share) in payees.iter().zip(ctx.remaining_accounts.iter()).zip(netting::split_evenly(amount, payees.len())) {
+            require!(payee_info.key == payee, ErrorCode::MissingAccount);
+            pay_from_job(job, payee_info, share)?;
+            msg!("Transferred {} lamports to payee {}", share, payee);
+        }
+
+        msg!("Milestone {} released ({} lamports)", index, amount);
+        Ok(())
+    }
+
+    /**
+     * Requires `threshold` of `approvers` to approve before funds can be distributed.
+     * The approver set cannot be changed once configured.
+     */
+    pub fn set_approvers(ctx: Context<SetApprovers>, approvers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        require!(!approvers.is_empty() && approvers.len() <= MAX_APPROVERS, ErrorCode::InvalidInput);
+        require!(threshold > 0 && threshold as usize <= approvers.len(), ErrorCode::InvalidInput);
+        for (i, approver) in approvers.iter().enumerate() {
+            require!(!approvers[..i].contains(approver), ErrorCode::InvalidInput);
+        }
+
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        require!(job.approvals.is_none(), ErrorCode::InvalidInput);
+
+        job.approvals = Some(Approvals { approvers, threshold, approved: 0 });
+
+        msg!("Distribution requires {} approvals", threshold);
+        Ok(())
+    }
+
+    /**
+     * Records the signer's approval to distribute. Approvals accumulate across
+     * transactions until the threshold is met.
+     */
+    pub fn approve_distribution(ctx: Context<ApproveDistribution>) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+
+        let signer = ctx.accounts.approver.key();
+        let approvals = job.approvals.as_mut().ok_or(error!(ErrorCode::NoApprovers))?;
+        let approver_index = approvals.approvers.iter()
+            .position(|a| *a == signer)
+            .ok_or(error!(ErrorCode::Unauthorized))?;
+        require!(approvals.approved & (1 << approver_index) == 0, ErrorCode::AlreadyApproved);
+
+        approvals.approved |= 1 << approver_index;
+
+        msg!("{} approved distribution ({}/{})", signer, approvals.approved.count_ones(), approvals.threshold);
+        Ok(())
+    }
+
+    /**
+     * Streams the collected funds to payees linearly between `start` and `end`
+     * instead of paying out at once.
+     */
+    pub fn set_vesting(ctx: Context<SetVesting>, start: i64, end: i64) -> Result<()> {
+        require!(end > start, ErrorCode::InvalidInput);
+
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        require!(job.vesting.is_none(), ErrorCode::InvalidInput);
+        require!(job.recurrence.is_none() && job.escrow.is_none() && job.milestones.is_none(), ErrorCode::InvalidInput);
+        require!(job.parent.is_none() && job.children.is_empty(), ErrorCode::InvalidInput);
+        require!(job.token.is_none() && job.accepted_assets.is_empty(), ErrorCode::UnsupportedForTokens);
+        require!(job.sponsorship.is_none() && job.crank_bounty == 0, ErrorCode::InvalidInput);
+
+        job.vesting = Some(Vesting {
+            start,
+            end,
+            total: 0,
+            claimed: vec![0; job.payees.len()],
+        });
+
+        msg!("Payout vests from {} to {}", start, end);
+        Ok(())
+    }
+
+    /**
+     * Pays the signing payee the portion of their share vested so far. The first claim
+     * locks the job against further payments and fixes the total being vested, so like
+     * distribution it waits for the deadline unless the payee may distribute early.
+     */
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        let payee_key = ctx.accounts.payee.key();
+        let clock = Clock::get()?;
+        let job = &mut ctx.accounts.job;
+
+        let payee_index = job.payees.iter()
+            .position(|p| *p == payee_key)
+            .ok_or(error!(ErrorCode::NotPayee))?;
+        let collected = job.collected();
+        let was_closed = job.closed;
+        let approved = job.distribution_approved();
+        let early = clock.unix_timestamp < job.distribution_deadline()
+            && !job.is_authorized(&payee_key, ROLE_DISTRIBUTE);
+        let num_payees = job.payees.len();
+
+        let schedule = job.vesting.as_mut().ok_or(error!(ErrorCode::NotVesting))?;
+
+        // The first claim locks in what was collected
+        if schedule.total == 0 {
+            require!(!was_closed, ErrorCode::AlreadyClosed);
+            require!(!early, ErrorCode::BeforeDeadline);
+            require!(approved, ErrorCode::NotApproved);
+            require!(collected > 0, ErrorCode::NothingToClaim);
+            schedule.total = collected;
+        }
+
+        let share = netting::split_evenly(schedule.total, num_payees)[payee_index];
+        let vested = vesting::vested_amount(share, schedule.start, schedule.end, clock.unix_timestamp);
+        let claimable = vested - schedule.claimed[payee_index];
+        require!(claimable > 0, ErrorCode::NothingToClaim);
+
+        schedule.claimed[payee_index] = vested;
+        job.closed = true;
+
+        pay_from_job(job, &ctx.accounts.payee, claimable)?;
+
+        msg!("{} claimed {} vested lamports", payee_key, claimable);
+        Ok(())
+    }
+
+    /**
+     * Adds a sponsor who matches each payment at `ratio_bps` of the job amount, up to
+     * `cap_per_payer`, from a pool deposited now. Must be added before anyone pays;
+     * whatever is left of the pool is returned to the sponsor on distribution.
+     */
+    pub fn add_sponsor(ctx: Context<AddSponsor>, ratio_bps: u16, cap_per_payer: u64, deposit: u64) -> Result<()> {
+        require!(ratio_bps > 0 && cap_per_payer > 0 && deposit > 0, ErrorCode::InvalidInput);
+
+        let job = &ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        require!(job.sponsorship.is_none(), ErrorCode::InvalidInput);
+        require!(job.recurrence.is_none() && job.milestones.is_none() && job.vesting.is_none(), ErrorCode::InvalidInput);
+        require!(job.itemization.is_none(), ErrorCode::InvalidInput);
+        require!(job.token.is_none() && job.accepted_assets.is_empty(), ErrorCode::UnsupportedForTokens);
+        require!(job.usd_pricing.is_none(), ErrorCode::UnsupportedForUsd);
+        require!(job.payers.iter().all(|p| !p.paid), ErrorCode::AlreadyPaid);
+
+        let sponsor_key = ctx.accounts.sponsor.key();
+        let job_key = job.key();
+
+        // Transfer the matching pool from the sponsor to the job account
+        invoke(
+            &system_instruction::transfer(
+                &sponsor_key,
+                &job_key,
+                deposit,
+            ),
+            &[
+                ctx.accounts.sponsor.to_account_info(),
+                ctx.accounts.job.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        ctx.accounts.job.sponsorship = Some(Sponsorship {
+            sponsor: sponsor_key,
+            ratio_bps,
+            cap_per_payer,
+            pool: deposit,
+            matched: 0,
+        });
+
+        msg!("{} sponsors the job with a {} lamport matching pool", sponsor_key, deposit);
+        Ok(())
+    }
+
+    /**
+     * Deposits a bounty paid to whoever distributes the job after its deadline, so
+     * third parties have a reason to crank settlement. Distributing is also how jobs
+     * nobody paid into are closed, so the bounty covers that path too; recurring
+     * jobs pay it once, for the first period settled.
+     */
+    pub fn set_crank_bounty(ctx: Context<SetCrankBounty>, bounty: u64) -> Result<()> {
+        require!(bounty > 0, ErrorCode::InvalidInput);
+
+        let job = &ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        require!(job.crank_bounty == 0, ErrorCode::InvalidInput);
+        require!(job.milestones.is_none() && job.vesting.is_none(), ErrorCode::InvalidInput);
+
+        let authority_key = ctx.accounts.authority.key();
+        let job_key = job.key();
+
+        // Transfer the bounty from the authority to the job account
+        invoke(
+            &system_instruction::transfer(
+                &authority_key,
+                &job_key,
+                bounty,
+            ),
+            &[
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.job.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        ctx.accounts.job.crank_bounty = bounty;
+
+        msg!("Crank bounty of {} lamports deposited", bounty);
+        Ok(())
+    }
+
+    /**
+     * Creates or updates the job's metadata account holding a title, description,
+     * category and external reference.
+     */
+    pub fn set_metadata(ctx: Context<SetMetadata>, title: String, description: String, category: String, reference: String) -> Result<()> {
+        require!(title.len() <= MAX_TITLE_LEN, ErrorCode::MetadataTooLong);
+        require!(description.len() <= MAX_DESCRIPTION_LEN, ErrorCode::MetadataTooLong);
+        require!(category.len() <= MAX_CATEGORY_LEN, ErrorCode::MetadataTooLong);
+        require!(reference.len() <= MAX_REFERENCE_LEN, ErrorCode::MetadataTooLong);
+
+        let metadata = &mut ctx.accounts.metadata;
+        metadata.job = ctx.accounts.job.key();
+        metadata.title = title;
+        metadata.description = description;
+        metadata.category = category;
+        metadata.reference = reference;
+
+        msg!("Metadata updated for job {}", metadata.job);
+        Ok(())
+    }
+
+    /**
+     * Turns the job into an itemized bill: instead of everyone owing `amount`, each payer
+     * owes the items they claim plus tax and tip (in basis points of their subtotal).
+     * Items are priced in lamports, so only SOL jobs where each payer owes their own
+     * amount can be itemized. Must be set before anyone pays.
+     */
+    pub fn set_items(ctx: Context<SetItems>, items: Vec<LineItemConfig>, tax_bps: u16, tip_bps: u16) -> Result<()> {
+        require!(!items.is_empty() && items.len() <= MAX_LINE_ITEMS, ErrorCode::InvalidInput);
+        for item in items.iter() {
+            require!(item.description.len() <= MAX_ITEM_DESCRIPTION_LEN, ErrorCode::MetadataTooLong);
+            require!(item.price > 0 && item.quantity != Some(0), ErrorCode::InvalidInput);
+        }
+
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        require!(job.itemization.is_none(), ErrorCode::InvalidInput);
+        require!(job.recurrence.is_none() && job.escrow.is_none() && job.sponsorship.is_none(), ErrorCode::InvalidInput);
+        require!(!job.split_total, ErrorCode::InvalidInput);
+        // Item prices are in lamports
+        require!(job.token.is_none() && job.accepted_assets.is_empty(), ErrorCode::UnsupportedForTokens);
+        require!(job.usd_pricing.is_none(), ErrorCode::UnsupportedForUsd);
+        require!(job.payers.iter().all(|p| !p.paid), ErrorCode::AlreadyPaid);
+
+        let num_payers = job.payers.len();
+        job.itemization = Some(Itemization {
+            items: items.into_iter().map(|item| LineItem {
+                description: item.description,
+                price: item.price,
+                quantity: item.quantity.unwrap_or(1),
+                claims: vec![0; num_payers],
+            }).collect(),
+            tax_bps,
+            tip_bps,
+            collected: 0,
+        });
+
+        msg!("Itemized bill set with {} items", job.itemization.as_ref().map_or(0, |i| i.items.len()));
+        Ok(())
+    }
+
+    /**
+     * Claims `bps` of a line item for the signing payer, replacing any earlier claim on
+     * it (0 drops the claim). Shared items are claimed in parts by several payers, and
+     * claims can only change until the payer pays.
+     */
+    pub fn claim_item(ctx: Context<ClaimItem>, index: u8, bps: u16) -> Result<()> {
+        require!(bps as u64 <= fees::BPS_DENOMINATOR, ErrorCode::InvalidInput);
+
+        let payer_key = ctx.accounts.payer.key();
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+
+        let payer_index = job.payers.iter()
+            .position(|ws| ws.wallet == payer_key)
+            .ok_or(error!(ErrorCode::NotContributor))?;
+        require!(!job.payers[payer_index].is_resolved(), ErrorCode::AlreadyPaid);
+
+        let itemization = job.itemization.as_mut().ok_or(error!(ErrorCode::NotItemized))?;
+        let item = itemization.items.get_mut(index as usize).ok_or(error!(ErrorCode::InvalidInput))?;
+        let claimed_by_others = item.claimed_bps() - item.claims[payer_index] as u64;
+        require!(claimed_by_others + bps as u64 <= fees::BPS_DENOMINATOR, ErrorCode::ItemOverclaimed);
+
+        item.claims[payer_index] = bps;
+
+        msg!("{} claimed {}% of {}", payer_key, bps as f64 / 100.0, item.description);
+        Ok(())
+    }
+
+    /**
+     * Lets payers of a SOL job pay in a token instead, at a fixed rate of `numerator /
+     * denominator` token base units per lamport owed. Each accepted token gets its own
+     * vault. Must be added before anyone pays.
+     */
+    pub fn add_accepted_asset(ctx: Context<AddAcceptedAsset>, numerator: u64, denominator: u64) -> Result<()> {
+        require!(numerator > 0 && denominator > 0, ErrorCode::InvalidInput);
+        check_mint_extensions(&ctx.accounts.mint.to_account_info())?;
+
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        require!(job.accepted_assets.len() < MAX_ACCEPTED_ASSETS, ErrorCode::InvalidInput);
+        require!(job.token.is_none(), ErrorCode::UnsupportedForTokens);
+        require!(job.usd_pricing.is_none(), ErrorCode::UnsupportedForUsd);
+        require!(job.recurrence.is_none() && job.escrow.is_none() && job.milestones.is_none(), ErrorCode::InvalidInput);
+        require!(job.vesting.is_none() && job.sponsorship.is_none() && job.itemization.is_none(), ErrorCode::InvalidInput);
+        require!(job.parent.is_none(), ErrorCode::InvalidInput);
+        require!(job.payers.iter().all(|p| !p.paid), ErrorCode::AlreadyPaid);
+
+        let mint = ctx.accounts.mint.key();
+        require!(job.accepted_assets.iter().all(|a| a.mint != mint), ErrorCode::InvalidInput);
+
+        job.accepted_assets.push(AcceptedAsset {
+            mint,
+            token_program: ctx.accounts.token_program.key(),
+            vault_bump: ctx.bumps.vault,
+            numerator,
+            denominator,
+            collected: 0,
+            distributed: false,
+        });
+
+        msg!("Job accepts {} at {}/{} per lamport", mint, numerator, denominator);
+        Ok(())
+    }
+
+    /**
+     * Pays the signer's share (including any late fee) in one of the job's accepted
+     * assets, converted at its fixed rate and grossed up for any transfer fee. Like
+     * `pay`, resolving the last payer fires the job's completion hook.
+     */
+    pub fn pay_in_asset<'info>(ctx: Context<'_, '_, 'info, 'info, PayInAsset<'info>>, asset_index: u8) -> Result<()> {
+        let job = &ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        require!(job.payments_open(), ErrorCode::PaymentsNotOpen);
+
+        let payer_key = ctx.accounts.payer.key();
+        let payer_index = job.payers.iter()
+            .position(|ws| ws.wallet == payer_key)
+            .ok_or(error!(ErrorCode::NotContributor))?;
+        let ws = &job.payers[payer_index];
+        require!(!ws.is_resolved(), ErrorCode::AlreadyPaid);
+        require!(ws.acceptance != Acceptance::Declined, ErrorCode::Declined);
+
+        let asset = job.accepted_assets.get(asset_index as usize).ok_or(error!(ErrorCode::InvalidInput))?;
+        require!(ctx.accounts.mint.key() == asset.mint && ctx.accounts.token_program.key() == asset.token_program, ErrorCode::WrongMint);
+
+        // Convert what is owed in lamports into the asset
+        let clock = Clock::get()?;
+        let owed = job.amount_owed(payer_index)?
+            .checked_add(job.late_fee_for(payer_index, clock.unix_timestamp)?)
+            .ok_or(error!(ErrorCode::MathOverflow))?;
+        let amount = pricing::convert(owed, asset.numerator, asset.denominator).ok_or(error!(ErrorCode::MathOverflow))?;
+        let gross = with_transfer_fee(&ctx.accounts.mint.to_account_info(), amount)?;
+
+        transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.payer_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            gross,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        let job = &mut ctx.accounts.job;
+        let asset = &mut job.accepted_assets[asset_index as usize];
+        asset.collected = asset.collected
+            .checked_add(amount)
+            .ok_or(error!(ErrorCode::MathOverflow))?;
+        let job_deadline = job.deadline;
+        let ws = &mut job.payers[payer_index];
+        ws.paid = true;
+        ws.acceptance = Acceptance::Accepted;
+        ws.paid_in = Some(asset_index);
+        let late = clock.unix_timestamp > ws.effective_deadline(job_deadline);
+        ws.paid_late = late;
+
+        // Track the payment on the payer's reputation profile, at its worth in lamports
+        if let Some(profile) = ctx.accounts.profile.as_mut() {
+            require!(!job.allow_withdrawals, ErrorCode::WithdrawalsAllowed);
+            let ws = &mut job.payers[payer_index];
+            profile.record_payment(owed, late, ws.late_recorded)?;
+            ws.late_recorded |= late;
+        }
+
+        msg!("{} paid {} of {} (worth {} lamports)", payer_key, amount, ctx.accounts.mint.key(), owed);
+        emit!(PaymentMade {
+            job: job.key(),
+            payer: payer_key,
+            amount: owed,
+            memo: None,
+        });
+
+        if job.payers.iter().all(|c| c.is_resolved()) {
+            let collected = job.collected();
+            fire_completion_hook(job, &ctx.accounts.hook_program, ctx.remaining_accounts, CompletionEvent::Funded, collected)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Splits an accepted asset's pool evenly across payees once the job was distributed.
+     * Payees' token accounts are passed as remaining accounts in `payees` order.
+     */
+    pub fn distribute_asset<'info>(ctx: Context<'_, '_, 'info, 'info, DistributeAsset<'info>>, asset_index: u8) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+        require!(job.closed, ErrorCode::NotDistributed);
+        require!(ctx.remaining_accounts.len() == job.payees.len(), ErrorCode::MissingAccount);
+
+        let job_key = job.key();
+        let mint_key = ctx.accounts.mint.key();
+        let asset = job.accepted_assets.get_mut(asset_index as usize).ok_or(error!(ErrorCode::InvalidInput))?;
+        require!(mint_key == asset.mint && ctx.accounts.token_program.key() == asset.token_program, ErrorCode::WrongMint);
+        require!(!asset.distributed, ErrorCode::AlreadyClosed);
+        asset.distributed = true;
+
+        let seeds: &[&[u8]] = &[b"vault", job_key.as_ref(), mint_key.as_ref(), &[asset.vault_bump]];
+        let shares = netting::split_evenly(asset.collected, job.payees.len());
+        for ((payee, payee_account), share) in job.payees.iter().zip(ctx.remaining_accounts.iter()).zip(shares) {
+            let destination = InterfaceAccount::<TokenAccount>::try_from(payee_account)?;
+            require!(destination.owner == *payee && destination.mint == mint_key, ErrorCode::WrongMint);
+
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: payee_account.clone(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                share,
+                ctx.accounts.mint.decimals,
+            )?;
+            msg!("Transferred {} of {} to payee {}", share, mint_key, payee);
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Links a child job that lists this job as a payee. Distributing the child pays
+     * the parent's share into it as a payment, and the parent pays out only once every
+     * linked child has been distributed. The signer must be the authority of both jobs.
+     * Children settle once, so they cannot recur, and parents cannot pay out through
+     * milestones or vesting, which never wait for their children. Jobs nest only one
+     * level deep, which rules out cycles.
+     */
+    pub fn add_child_job(ctx: Context<AddChildJob>) -> Result<()> {
+        let parent_key = ctx.accounts.parent.key();
+        let child_key = ctx.accounts.child.key();
+        require!(parent_key != child_key, ErrorCode::InvalidInput);
+
+        // Jobs nest one level deep, so a job can't end up feeding itself
+        require!(ctx.accounts.child.children.is_empty() && ctx.accounts.parent.parent.is_none(), ErrorCode::InvalidInput);
+
+        let child = &mut ctx.accounts.child;
+        require!(!child.closed, ErrorCode::AlreadyClosed);
+        require!(child.parent.is_none(), ErrorCode::InvalidInput);
+        require!(child.payees.contains(&parent_key), ErrorCode::InvalidInput);
+        require!(child.recurrence.is_none() && child.milestones.is_none() && child.vesting.is_none(), ErrorCode::InvalidInput);
+        require!(child.token.is_none() && child.accepted_assets.is_empty(), ErrorCode::UnsupportedForTokens);
+        child.parent = Some(parent_key);
+
+        let parent = &mut ctx.accounts.parent;
+        require!(!parent.closed, ErrorCode::AlreadyClosed);
+        require!(parent.children.len() < MAX_CHILD_JOBS, ErrorCode::InvalidInput);
+        require!(parent.recurrence.is_none() && parent.milestones.is_none() && parent.vesting.is_none(), ErrorCode::InvalidInput);
+        require!(parent.token.is_none(), ErrorCode::UnsupportedForTokens);
+        parent.children.push(ChildJob { job: child_key, settled: false, credited: 0 });
+
+        msg!("Job {} now feeds parent job {}", child_key, parent_key);
+        Ok(())
+    }
+
+    /**
+     * Creates the signer's reputation profile, tracking how reliably they pay across jobs.
+     */
+    pub fn create_profile(ctx: Context<CreateProfile>) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        profile.wallet = ctx.accounts.wallet.key();
+        profile.jobs_paid = 0;
+        profile.on_time = 0;
+        profile.late = 0;
+        profile.total_paid = 0;
+
+        msg!("Profile created for {}", profile.wallet);
+        Ok(())
+    }
+
+    /**
+     * Records on a payer's profile that their deadline passed without payment, or that
+     * they paid late without passing their profile. Anyone may call this, and each missed
+     * deadline counts once even if the payer pays later.
+     */
+    pub fn record_late(ctx: Context<RecordLate>) -> Result<()> {
+        let clock = Clock::get()?;
+        let job = &mut ctx.accounts.job;
+        let profile = &mut ctx.accounts.profile;
+        let closed = job.closed;
+
+        let job_deadline = job.deadline;
+        let ws = job.payers.iter_mut()
+            .find(|ws| ws.wallet == profile.wallet)
+            .ok_or(error!(ErrorCode::NotContributor))?;
+        if !ws.paid_late {
+            require!(!closed, ErrorCode::AlreadyClosed);
+            require!(!ws.is_resolved(), ErrorCode::AlreadyPaid);
+            require!(clock.unix_timestamp > ws.effective_deadline(job_deadline), ErrorCode::BeforeDeadline);
+        }
+        require!(!ws.late_recorded, ErrorCode::AlreadyRecorded);
+
+        ws.late_recorded = true;
+        profile.late += 1;
+
+        msg!("Recorded missed deadline for {}", profile.wallet);
+        Ok(())
+    }
+
+    /**
+     * Pays for upcoming periods of a recurring job in advance. Each prepaid period
+     * marks the payer as paid when the next period starts.
+     */
+    pub fn prepay(ctx: Context<Pay>, periods: u16) -> Result<()> {
+        require!(periods > 0, ErrorCode::InvalidInput);
+        require!(!ctx.accounts.job.closed, ErrorCode::AlreadyClosed);
+        require!(ctx.accounts.job.recurrence.is_some(), ErrorCode::NotRecurring);
+        require!(ctx.accounts.job.token.is_none(), ErrorCode::UnsupportedForTokens);
+
+        let payer_key = ctx.accounts.payer.key();
+        let payer_index = ctx.accounts.job.payers.iter()
+            .position(|ws| ws.wallet == payer_key)
+            .ok_or(error!(ErrorCode::NotContributor))?;
+        require!(ctx.accounts.job.payers[payer_index].is_resolved(), ErrorCode::NotPaid);
+
+        let job_key = ctx.accounts.job.key();
+        let amount = ctx.accounts.job.share_of(payer_index)
+            .checked_mul(periods as u64)
+            .ok_or(error!(ErrorCode::MathOverflow))?;
+
+        // Transfer SOL from payer to job account
+        invoke(
+            &system_instruction::transfer(
+                &payer_key,
+                &job_key,
+                amount,
+            ),
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.job.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        let ws = &mut ctx.accounts.job.payers[payer_index];
+        ws.prepaid_periods = ws.prepaid_periods
+            .checked_add(periods)
+            .ok_or(error!(ErrorCode::MathOverflow))?;
+        msg!("{} prepaid {} periods ({} lamports)", payer_key, periods, amount);
+
+        Ok(())
+    }
+
+    /**
+     * Extends the job deadline, or a single payer's deadline when `payer` is given.
+     * Deadlines can only be pushed later, never shortened. Operators may only extend
+     * payers' deadlines, up to `MAX_OPERATOR_EXTENSION` past the job deadline.
+     */
+    pub fn extend_deadline(ctx: Context<ExtendDeadline>, new_deadline: i64, payer: Option<Pubkey>) -> Result<()> {
+        let signer = ctx.accounts.authority.key();
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+
+        match payer {
+            Some(payer_key) => {
+                let job_deadline = job.deadline;
+                require!(
+                    signer == job.authority || new_deadline <= job_deadline.saturating_add(MAX_OPERATOR_EXTENSION),
+                    ErrorCode::ExtensionTooLong
+                );
+                let ws = job.payers.iter_mut()
+                    .find(|ws| ws.wallet == payer_key)
+                    .ok_or(error!(ErrorCode::NotContributor))?;
+                require!(new_deadline > ws.effective_deadline(job_deadline), ErrorCode::DeadlineNotExtended);
+
+                ws.deadline = Some(new_deadline);
+                msg!("Deadline for {} extended to {}", payer_key, new_deadline);
+            }
+            None => {
+                // Operators may only give individual payers more time
+                require!(signer == job.authority, ErrorCode::Unauthorized);
+                require!(new_deadline > job.deadline, ErrorCode::DeadlineNotExtended);
+
+                job.deadline = new_deadline;
+                msg!("Job deadline extended to {}", new_deadline);
+            }
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Records that a payer settled their share outside the program (e.g. in cash).
+     * Settled payers no longer owe anything but are not counted as collected funds.
+     * Settling the last outstanding payer fires the completion hook.
+     */
+    pub fn mark_settled<'info>(ctx: Context<'_, '_, 'info, 'info, MarkSettled<'info>>, payer: Pubkey, note: Option<String>) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        if let Some(note) = &note {
+            require!(note.len() <= MAX_SETTLEMENT_NOTE_LEN, ErrorCode::NoteTooLong);
+        }
+
+        let ws = job.payers.iter_mut()
+            .find(|ws| ws.wallet == payer)
+            .ok_or(error!(ErrorCode::NotContributor))?;
+        require!(!ws.is_resolved(), ErrorCode::AlreadyPaid);
+
+        ws.settled_off_chain = true;
+        ws.settlement_note = note;
+        msg!("{} marked as settled off-chain", payer);
+
+        if job.payers.iter().all(|c| c.is_resolved()) {
+            let collected = job.collected();
+            fire_completion_hook(job, &ctx.accounts.hook_program, ctx.remaining_accounts, CompletionEvent::Funded, collected)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Grants `wallet` a set of `ROLE_*` flags on the job, replacing any it had.
+     * Passing no roles revokes the wallet's access.
+     */
+    pub fn set_operator(ctx: Context<SetOperator>, wallet: Pubkey, roles: u8) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+        require!(roles & !ALL_ROLES == 0, ErrorCode::InvalidInput);
+        require!(wallet != job.authority, ErrorCode::InvalidInput);
+
+        match job.operators.iter().position(|o| o.wallet == wallet) {
+            Some(i) if roles == 0 => {
+                job.operators.remove(i);
+            }
+            Some(i) => job.operators[i].roles = roles,
+            None if roles == 0 => return err!(ErrorCode::InvalidInput),
+            None => {
+                require!(job.operators.len() < MAX_OPERATORS, ErrorCode::InvalidInput);
+                job.operators.push(Operator { wallet, roles });
+            }
+        }
+
+        msg!("Roles of {} set to {:#05b}", wallet, roles);
+        Ok(())
+    }
+
+    /**
+     * Adds a payer owing the job's per-payer amount. Not available for itemized jobs,
+     * whose claims are tracked per payer, or split-total jobs, whose payers must cover
+     * the bill exactly.
+     */
+    pub fn add_payer(ctx: Context<AddPayer>, wallet: Pubkey) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        require!(job.itemization.is_none() && !job.split_total, ErrorCode::InvalidInput);
+        require!(job.payers.iter().all(|p| p.wallet != wallet), ErrorCode::InvalidInput);
+        if job.recurrence.is_some() {
+            require!(job.payers.len() < MAX_RECURRING_PAYERS, ErrorCode::InvalidInput);
+        }
+        if job.escrow.is_some() {
+            require!(job.payers.len() < MAX_ESCROW_PAYERS, ErrorCode::InvalidInput);
+        }
+
+        job.payers.push(WalletStatus { wallet, paid: false, deadline: None, settled_off_chain: false, settlement_note: None, acceptance: Acceptance::Pending, prepaid_periods: 0, matched: 0, memo: None, late_recorded: false, paid_late: false, paid_in: None });
+
+        msg!("{} added as a payer", wallet);
+        Ok(())
+    }
+
+    /**
+     * Removes a payer who has not paid or settled. Jobs that track payers by position
+     * (escrow, recurring and itemized jobs) and split-total jobs can't remove payers.
+     */
+    pub fn remove_payer(ctx: Context<RemovePayer>, wallet: Pubkey) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        require!(job.escrow.is_none() && job.recurrence.is_none() && job.itemization.is_none(), ErrorCode::InvalidInput);
+        require!(!job.split_total, ErrorCode::InvalidInput);
+        require!(job.payers.len() > 1, ErrorCode::InvalidInput);
+
+        let i = job.payers.iter()
+            .position(|p| p.wallet == wallet)
+            .ok_or(error!(ErrorCode::NotContributor))?;
+        require!(!job.payers[i].is_resolved(), ErrorCode::AlreadyPaid);
+        job.payers.remove(i);
+
+        msg!("{} removed from the payers", wallet);
+        Ok(())
+    }
+
+    /**
+     * Registers a program to notify when the job is fully funded and when it is
+     * distributed, or clears it when `program` is None. See `CompletionHook` for the
+     * interface the program must implement.
+     */
+    pub fn set_completion_hook(ctx: Context<SetCompletionHook>, program: Option<Pubkey>, accounts: Vec<HookAccount>) -> Result<()> {
+        let job_key = ctx.accounts.job.key();
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+
+        let Some(program) = program else {
+            require!(accounts.is_empty(), ErrorCode::InvalidInput);
+            job.completion_hook = None;
+            msg!("Completion hook cleared");
+            return Ok(());
+        };
+        require!(program != crate::ID, ErrorCode::InvalidInput);
+        require!(accounts.len() <= MAX_HOOK_ACCOUNTS, ErrorCode::InvalidInput);
+        require!(accounts.iter().all(|a| a.pubkey != job_key), ErrorCode::InvalidInput);
+
+        job.completion_hook = Some(CompletionHook { program, accounts });
+        msg!("Completion hook set to program {}", program);
+        Ok(())
+    }
+
+    /**
+     * Lets a paid payer pull their contribution back before their deadline,
+     * when the job allows withdrawals.
+     */
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+        require!(!job.closed, ErrorCode::AlreadyClosed);
+        require!(job.allow_withdrawals, ErrorCode::WithdrawalsNotAllowed);
+        require!(job.token.is_none(), ErrorCode::UnsupportedForTokens);
+        require!(job.usd_pricing.is_none(), ErrorCode::UnsupportedForUsd);
+
+        let payer_key = ctx.accounts.payer.key();
+        let job_deadline = job.deadline;
+        let payer_index = job.payers.iter()
+            .position(|ws| ws.wallet == payer_key)
+            .ok_or(error!(ErrorCode::NotContributor))?;
+        let amount = job.amount_owed(payer_index)?;
+        let ws = &mut job.payers[payer_index];
+        require!(ws.paid, ErrorCode::NotPaid);
+        require!(ws.paid_in.is_none(), ErrorCode::UnsupportedForTokens);
+
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp < ws.effective_deadline(job_deadline), ErrorCode::AfterDeadline);
+
+        // Reset the payer so they owe the job again
+        ws.paid = false;
+        ws.paid_late = false;
+        ws.memo = None;
+        let matched = std::mem::take(&mut ws.matched);
+
+        // Their sponsor match goes back into the pool
+        if let Some(sponsorship) = job.sponsorship.as_mut() {
+            sponsorship.pool += matched;
+            sponsorship.matched -= matched;
+        }
+
+        // Itemized payers may change their claims and pay again
+        if let Some(itemization) = job.itemization.as_mut() {
+            itemization.collected -= amount;
+        }
+
+        // A confirmation only counts while the payer's funds are in escrow
+        if let Some(escrow) = job.escrow.as_mut() {
+            escrow.confirmations &= !(1 << payer_index);
+        }
+
+        pay_from_job(job, &ctx.accounts.payer, amount)?;
+
+        emit!(PledgeWithdrawn {
+            job: job.key(),
+            payer: payer_key,
+            amount,
+        });
+        msg!("{} withdrew {} lamports", payer_key, amount);
+
+        Ok(())
+    }
+
+    /**
+     * Lets a listed payer accept the obligation. Payments on jobs that require
+     * acceptance only open once every payer has accepted or declined.
+     */
+    pub fn accept(ctx: Context<RespondToJob>) -> Result<()> {
+        respond(&mut ctx.accounts.job, ctx.accounts.payer.key(), Acceptance::Accepted)?;
+        msg!("{} accepted the payment job", ctx.accounts.payer.key());
+        Ok(())
+    }
+
+    /**
+     * Lets a listed payer decline the obligation. Declined payers cannot pay
+     * and are not waited on for distribution.
+     */
+    pub fn decline(ctx: Context<RespondToJob>) -> Result<()> {
+        respond(&mut ctx.accounts.job, ctx.accounts.payer.key(), Acceptance::Declined)?;
+        msg!("{} declined the payment job", ctx.accounts.payer.key());
+        Ok(())
+    }
+
+    /**
+     * Creates a persistent friend group whose members keep running balances
+     * across shared expenses.
+     */
+    pub fn create_group(ctx: Context<CreateGroup>, members: Vec<Pubkey>) -> Result<()> {
+        require!(members.len() > 1, ErrorCode::InvalidInput);
+        require!(members.len() <= MAX_GROUP_MEMBERS, ErrorCode::InvalidInput);
+        for (i, member) in members.iter().enumerate() {
+            require!(!members[..i].contains(member), ErrorCode::InvalidInput);
+        }
+
+        let group = &mut ctx.accounts.group;
+        group.authority = ctx.accounts.authority.key();
+        group.members = members.into_iter().map(|pk| GroupMember { wallet: pk, balance: 0 }).collect();
+
+        msg!("Group created with {} members", group.members.len());
+        Ok(())
+    }
+
+    /**
+     * Records an expense fronted by one member and shared evenly by others.
+     * Remainder lamports are assigned to the first sharers so balances stay zero-sum.
+     * Members can only record what they fronted themselves; the group authority
+     * can record on anyone's behalf.
+     */
+    pub fn record_expense(ctx: Context<RecordExpense>, amount: u64, fronted_by: Pubkey, shared_by: Vec<Pubkey>) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidInput);
+        require!(!shared_by.is_empty(), ErrorCode::InvalidInput);
+
+        let group = &mut ctx.accounts.group;
+        let member = ctx.accounts.member.key();
+        if member != group.authority {
+            require!(group.member_index(&member).is_some(), ErrorCode::NotMember);
+            require!(fronted_by == member, ErrorCode::Unauthorized);
+        }
+
+        let amount = i64::try_from(amount).map_err(|_| error!(ErrorCode::MathOverflow))?;
+        let sharers = shared_by.len() as i64;
+        let share = amount / sharers;
+        let remainder = amount % sharers;
+
+        let fronter_index = group.member_index(&fronted_by).ok_or(error!(ErrorCode::NotMember))?;
+        group.members[fronter_index].balance = group.members[fronter_index].balance
+            .checked_add(amount)
+            .ok_or(error!(ErrorCode::MathOverflow))?;
+
+        for (i, sharer) in shared_by.iter().enumerate() {
+            require!(!shared_by[..i].contains(sharer), ErrorCode::InvalidInput);
+            let sharer_index = group.member_index(sharer).ok_or(error!(ErrorCode::NotMember))?;
+            let owed = if (i as i64) < remainder { share + 1 } else { share };
+            group.members[sharer_index].balance = group.members[sharer_index].balance
+                .checked_sub(owed)
+                .ok_or(error!(ErrorCode::MathOverflow))?;
+        }
+
+        msg!("{} fronted {} lamports shared by {} members", fronted_by, amount, shared_by.len());
+        Ok(())
+    }
+
+    /**
+     * Pays down the signer's net debt by sending lamports to a member who is owed.
+     */
+    pub fn settle(ctx: Context<Settle>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidInput);
+
+        let debtor_key = ctx.accounts.debtor.key();
+        let creditor_key = ctx.accounts.creditor.key();
+        let group = &ctx.accounts.group;
+        let debtor_index = group.member_index(&debtor_key).ok_or(error!(ErrorCode::NotMember))?;
+        let creditor_index = group.member_index(&creditor_key).ok_or(error!(ErrorCode::NotMember))?;
+
+        // Settlements can only move balances towards zero
+        let signed_amount = i64::try_from(amount).map_err(|_| error!(ErrorCode::MathOverflow))?;
+        require!(group.members[debtor_index].balance <= -signed_amount, ErrorCode::SettlementTooLarge);
+        require!(group.members[creditor_index].balance >= signed_amount, ErrorCode::SettlementTooLarge);
+
+        // Transfer SOL straight from debtor to creditor
+        invoke(
+            &system_instruction::transfer(
+                &debtor_key,
+                &creditor_key,
+                amount,
+            ),
+            &[
+                ctx.accounts.debtor.to_account_info(),
+                ctx.accounts.creditor.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        let group = &mut ctx.accounts.group;
+        group.members[debtor_index].balance += signed_amount;
+        group.members[creditor_index].balance -= signed_amount;
+
+        msg!("{} settled {} lamports with {}", debtor_key, amount, creditor_key);
+        Ok(())
+    }
+
+    /**
+     * Settles every outstanding obligation of several jobs at once with a netted
+     * transfer plan. Remaining accounts are the `num_jobs` job accounts followed by
+     * every payer and payee being settled, who must all sign since their claims are
+     * netted away. Jobs that gate payouts behind escrow, milestones, vesting or
+     * approvals cannot be netted, as this pays payees directly.
+     */
+    pub fn settle_netted<'info>(ctx: Context<'_, '_, 'info, 'info, SettleNetted<'info>>, num_jobs: u8, transfers: Vec<NettedTransfer>) -> Result<()> {
+        let num_jobs = num_jobs as usize;
+        require!(num_jobs > 0, ErrorCode::InvalidInput);
+        require!(ctx.remaining_accounts.len() >= num_jobs, ErrorCode::MissingAccount);
+        let (job_infos, wallet_infos) = ctx.remaining_accounts.split_at(num_jobs);
+        let clock = Clock::get()?;
+
+        // Gross obligations of every unresolved payer towards the payees of each job
+        let mut jobs = Vec::with_capacity(num_jobs);
+        let mut obligations = Vec::new();
+        for (i, job_info) in job_infos.iter().enumerate() {
+            require!(!job_infos[..i].iter().any(|j| j.key == job_info.key), ErrorCode::InvalidInput);
+            let job = Account::<PaymentJob>::try_from(job_info)?;
+            require!(!job.closed, ErrorCode::AlreadyClosed);
+            require!(job.payments_open(), ErrorCode::PaymentsNotOpen);
+            require!(job.token.is_none(), ErrorCode::UnsupportedForTokens);
+            require!(job.usd_pricing.is_none(), ErrorCode::UnsupportedForUsd);
+            require!(
+                job.escrow.is_none() && job.milestones.is_none() && job.vesting.is_none() && job.approvals.is_none(),
+                ErrorCode::UnsupportedForNetting
+            );
+
+            for (payer_index, ws) in job.payers.iter().enumerate() {
+                if ws.is_resolved() {
+                    continue;
+                }
+                let owed = job.amount_owed(payer_index)?
+                    .checked_add(job.late_fee_for(payer_index, clock.unix_timestamp)?)
+                    .ok_or(error!(ErrorCode::MathOverflow))?;
+                for (payee, share) in job.payees.iter().zip(netting::split_evenly(owed, job.payees.len())) {
+                    obligations.push(Obligation { from: ws.wallet, to: *payee, amount: share });
+                }
+            }
+            jobs.push(job);
+        }
+
+        // The plan must leave every participant exactly even
+        let plan: Vec<Transfer<Pubkey>> = transfers.iter()
+            .map(|t| Transfer { from: t.from, to: t.to, amount: t.amount })
+            .collect();
+        require!(transfers.iter().all(|t| t.amount > 0), ErrorCode::InvalidInput);
+        require!(netting::settles(&obligations, &plan), ErrorCode::InvalidNettingPlan);
+
+        // Everyone whose obligation is netted must agree to it
+        for wallet in obligations.iter().flat_map(|o| [o.from, o.to]) {
+            let info = wallet_infos.iter()
+                .find(|w| *w.key == wallet)
+                .ok_or(error!(ErrorCode::MissingAccount))?;
+            require!(info.is_signer, ErrorCode::MissingSigner);
+        }
+
+        // Transfer SOL directly between participants
+        for t in transfers.iter() {
+            let from = wallet_infos.iter()
+                .find(|w| *w.key == t.from)
+                .ok_or(error!(ErrorCode::MissingAccount))?;
+            let to = wallet_infos.iter()
+                .find(|w| *w.key == t.to)
+                .ok_or(error!(ErrorCode::MissingAccount))?;
+            require!(from.is_signer, ErrorCode::MissingSigner);
+
+            invoke(
+                &system_instruction::transfer(&t.from, &t.to, t.amount),
+                &[
+                    from.clone(),
+                    to.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+            msg!("Transferred {} lamports from {} to {}", t.amount, t.from, t.to);
+        }
+
+        // Funds never passed through the jobs, so record them as settled outside the job account
+        for mut job in jobs {
+            for ws in job.payers.iter_mut().filter(|ws| !ws.is_resolved()) {
+                ws.settled_off_chain = true;
+                ws.settlement_note = Some(NETTED_SETTLEMENT_NOTE.to_string());
+            }
+            job.exit(&crate::ID)?;
+        }
+
+        msg!("Settled {} jobs with {} netted transfers", num_jobs, transfers.len());
+        Ok(())
+    }
+}
+
+// Moves lamports out of a job. The job account is owned by this program, so its
+// lamports are moved directly rather than through the system program.
+fn pay_from_job<'info>(job: &impl Lamports<'info>, to: &impl Lamports<'info>, amount: u64) -> Result<()> {
+    job.sub_lamports(amount)?;
+    to.add_lamports(amount)?;
+    Ok(())
+}
+
+// Records a payer's response to being listed on a job
+fn respond(job: &mut PaymentJob, payer: Pubkey, acceptance: Acceptance) -> Result<()> {
+    require!(!job.closed, ErrorCode::AlreadyClosed);
+    // Once payments open a payer can no longer back out from under the others
+    require!(!(job.require_acceptance && job.payments_open()), ErrorCode::ResponsesClosed);
+
+    let ws = job.payers.iter_mut()
+        .find(|ws| ws.wallet == payer)
+        .ok_or(error!(ErrorCode::NotContributor))?;
+    require!(!ws.paid && !ws.settled_off_chain, ErrorCode::AlreadyPaid);
+
+    ws.acceptance = acceptance;
+    Ok(())
+}
+
+// Rejects Token-2022 mints a token job cannot work with: non-transferable mints cannot be
+// paid in, transfer hooks need extra accounts, and a permanent delegate could drain the vault
+fn check_mint_extensions(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        require!(
+            !matches!(extension, ExtensionType::NonTransferable | ExtensionType::TransferHook | ExtensionType::PermanentDelegate),
+            ErrorCode::UnsupportedMint
+        );
+    }
+    Ok(())
+}
+
+// Amount to send so that `amount` arrives after the mint's transfer fee, if it has one
+fn with_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(amount);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let Ok(fee_config) = state.get_extension::<TransferFeeConfig>() else {
+        return Ok(amount);
+    };
+    let fee = fee_config
+        .calculate_inverse_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(error!(ErrorCode::MathOverflow))?;
+    amount.checked_add(fee).ok_or(error!(ErrorCode::MathOverflow))
+}
+
+// Mint and token program passed for a token job, checked against its configuration
+fn token_accounts<'a, 'info>(
+    token: &TokenConfig,
+    mint: &'a Option<InterfaceAccount<'info, Mint>>,
+    token_program: &'a Option<Interface<'info, TokenInterface>>,
+) -> Result<(&'a InterfaceAccount<'info, Mint>, &'a Interface<'info, TokenInterface>)> {
+    let mint = mint.as_ref().ok_or(error!(ErrorCode::MissingAccount))?;
+    let token_program = token_program.as_ref().ok_or(error!(ErrorCode::MissingAccount))?;
+    require!(mint.key() == token.mint && token_program.key() == token.token_program, ErrorCode::WrongMint);
+    Ok((mint, token_program))
+}
+
+// Invoke the job's completion hook, if any. The hook's accounts are the last remaining accounts.
+fn fire_completion_hook<'info>(
+    job: &mut Account<'info, PaymentJob>,
+    hook_program: &Option<UncheckedAccount<'info>>,
+    remaining_accounts: &[AccountInfo<'info>],
+    event: CompletionEvent,
+    collected: u64,
+) -> Result<()> {
+    let Some(hook) = job.completion_hook.clone() else {
+        return Ok(());
+    };
+    let program = hook_program.as_ref().ok_or(error!(ErrorCode::MissingAccount))?;
+    require!(program.key() == hook.program, ErrorCode::WrongHookAccounts);
+    require!(remaining_accounts.len() >= hook.accounts.len(), ErrorCode::MissingAccount);
+    let hook_accounts = &remaining_accounts[remaining_accounts.len() - hook.accounts.len()..];
+    for (info, expected) in hook_accounts.iter().zip(hook.accounts.iter()) {
+        require!(info.key() == expected.pubkey, ErrorCode::WrongHookAccounts);
+    }
+
+    // Persist the job first so the receiver reads its current state
+    job.exit(&crate::ID)?;
+
+    let mut metas = vec![AccountMeta::new_readonly(job.key(), false)];
+    metas.extend(hook.accounts.iter().map(|a| {
+        if a.is_writable {
+            AccountMeta::new(a.pubkey, false)
+        } else {
+            AccountMeta::new_readonly(a.pubkey, false)
+        }
+    }));
+    let mut data = COMPLETION_HOOK_DISCRIMINATOR.to_vec();
+    CompletionHookArgs { job: job.key(), event, collected }.serialize(&mut data)?;
+
+    let mut infos = vec![job.to_account_info()];
+    infos.extend(hook_accounts.iter().cloned());
+    infos.push(program.to_account_info());
+    invoke(&Instruction { program_id: hook.program, accounts: metas, data }, &infos)?;
+
+    msg!("Notified completion hook {}", hook.program);
+    Ok(())
+}
+
+// ==================== Account Structs ====================
+
+#[derive(Accounts)]
+#[instruction(
+    payers: Vec<Pubkey>,
+    payee: Vec<Pubkey>,
+    amount: u64,
+    deadline: Option<u64>,
+    late_fee: Option<LateFee>,
+    require_acceptance: bool,
+    allow_withdrawals: bool,
+    period: Option<i64>,
+)]
+pub struct CreatePaymentJob<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = PaymentJob::space(payers.len(), payee.len()) + if period.is_some() { Recurrence::SIZE } else { 0 },
+    )]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Mint of a token job, legacy or Token-2022
+    #[account(mint::token_program = token_program)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vault", job.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault,
+        token::token_program = token_program,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Pyth-format price account of a USD job, parsed when used
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Pay<'info> {
+    #[account(mut)]
+    pub job: Account<'info, PaymentJob>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, seeds = [b"profile", payer.key().as_ref()], bump)]
+    pub profile: Option<Account<'info, Profile>>,
+
+    /// Required for token jobs
+    #[account(mut)]
+    pub payer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [b"vault", job.key().as_ref()], bump)]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: must match `job.usd_pricing.price_feed`, required for USD jobs
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: must match `job.completion_hook.program`, required when the payment completes the job
+    pub hook_program: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeFunds<'info> {
+    #[account(mut)]
+    pub job: Account<'info, PaymentJob>,
+
+    /// CHECK: whoever triggers distribution, receives the crank bounty when signing
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+
+    /// CHECK: must match `job.sponsorship.sponsor`, required for sponsored jobs
+    #[account(mut)]
+    pub sponsor: Option<UncheckedAccount<'info>>,
+
+    /// Required for token jobs
+    #[account(mut, seeds = [b"vault", job.key().as_ref()], bump)]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// Required for child jobs
+    #[account(mut)]
+    pub parent: Option<Account<'info, PaymentJob>>,
+
+    /// CHECK: must match `job.completion_hook.program`, required for jobs with a hook
+    /// until `HOOK_GRACE_PERIOD` after the deadline (always when privileged)
+    pub hook_program: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendDeadline<'info> {
+    #[account(mut, constraint = job.is_authorized(&authority.key(), ROLE_EDIT_PAYERS) @ ErrorCode::Unauthorized)]
+    pub job: Account<'info, PaymentJob>,
+
+    /// The job authority, or an operator allowed to edit payers
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MarkSettled<'info> {
+    #[account(mut, constraint = job.is_authorized(&authority.key(), ROLE_MARK_SETTLED) @ ErrorCode::Unauthorized)]
+    pub job: Account<'info, PaymentJob>,
+
+    /// The job authority, or an operator allowed to mark payers settled
+    pub authority: Signer<'info>,
+
+    /// CHECK: must match `job.completion_hook.program`, required when settling the last payer
+    pub hook_program: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey, roles: u8)]
+pub struct SetOperator<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        realloc = job.to_account_info().data_len() + if roles == 0 || job.operators.iter().any(|o| o.wallet == wallet) { 0 } else { Operator::SIZE },
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(program: Option<Pubkey>, accounts: Vec<HookAccount>)]
+pub struct SetCompletionHook<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        realloc = job.to_account_info().data_len() + if program.is_some() { CompletionHook::space(accounts.len()) } else { 0 },
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddPayer<'info> {
+    #[account(
+        mut,
+        constraint = job.is_authorized(&authority.key(), ROLE_EDIT_PAYERS) @ ErrorCode::Unauthorized,
+        realloc = job.to_account_info().data_len() + WalletStatus::SIZE,
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub job: Account<'info, PaymentJob>,
+
+    /// The job authority, or an operator allowed to edit payers
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemovePayer<'info> {
+    #[account(mut, constraint = job.is_authorized(&authority.key(), ROLE_EDIT_PAYERS) @ ErrorCode::Unauthorized)]
+    pub job: Account<'info, PaymentJob>,
+
+    /// The job authority, or an operator allowed to edit payers
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RespondToJob<'info> {
+    #[account(mut)]
+    pub job: Account<'info, PaymentJob>,
+
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(members: Vec<Pubkey>)]
+pub struct CreateGroup<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Group::space(members.len()),
+    )]
+    pub group: Account<'info, Group>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordExpense<'info> {
+    #[account(mut)]
+    pub group: Account<'info, Group>,
+
+    pub member: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Settle<'info> {
+    #[account(mut)]
+    pub group: Account<'info, Group>,
+
+    #[account(mut)]
+    pub debtor: Signer<'info>,
+
+    /// CHECK: must be a group member, checked against `group.members`
+    #[account(mut)]
+    pub creditor: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleNetted<'info> {
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EnableEscrow<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        realloc = job.to_account_info().data_len() + Escrow::SIZE,
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EscrowAction<'info> {
+    #[account(mut)]
+    pub job: Account<'info, PaymentJob>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(stages: Vec<MilestoneConfig>)]
+pub struct SetMilestones<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        realloc = job.to_account_info().data_len() + Milestones::space(stages.len()),
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseMilestone<'info> {
+    #[account(mut)]
+    pub job: Account<'info, PaymentJob>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(approvers: Vec<Pubkey>)]
+pub struct SetApprovers<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        realloc = job.to_account_info().data_len() + Approvals::space(approvers.len()),
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetVesting<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        realloc = job.to_account_info().data_len() + Vesting::space(job.payees.len()),
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub payee: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddSponsor<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        realloc = job.to_account_info().data_len() + Sponsorship::SIZE,
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddChildJob<'info> {
+    #[account(
+        mut,
+        constraint = parent.authority == authority.key() @ ErrorCode::Unauthorized,
+        realloc = parent.to_account_info().data_len() + ChildJob::SIZE,
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub parent: Account<'info, PaymentJob>,
+
+    #[account(mut, constraint = child.authority == authority.key() @ ErrorCode::Unauthorized)]
+    pub child: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCrankBounty<'info> {
+    #[account(mut, has_one = authority @ ErrorCode::Unauthorized)]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMetadata<'info> {
+    #[account(has_one = authority @ ErrorCode::Unauthorized)]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = JobMetadata::SIZE,
+        seeds = [b"metadata", job.key().as_ref()],
+        bump,
+    )]
+    pub metadata: Account<'info, JobMetadata>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(items: Vec<LineItemConfig>)]
+pub struct SetItems<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        realloc = job.to_account_info().data_len() + Itemization::space(items.len(), job.payers.len()),
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimItem<'info> {
+    #[account(mut)]
+    pub job: Account<'info, PaymentJob>,
+
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddAcceptedAsset<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        realloc = job.to_account_info().data_len() + AcceptedAsset::SIZE,
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vault", job.key().as_ref(), mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault,
+        token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PayInAsset<'info> {
+    #[account(mut)]
+    pub job: Account<'info, PaymentJob>,
+
+    pub payer: Signer<'info>,
+
+    #[account(mut, seeds = [b"profile", payer.key().as_ref()], bump)]
+    pub profile: Option<Account<'info, Profile>>,
+
+    #[account(mut)]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"vault", job.key().as_ref(), mint.key().as_ref()], bump)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: must match `job.completion_hook.program`, required when the payment completes the job
+    pub hook_program: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeAsset<'info> {
+    #[account(mut)]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut, seeds = [b"vault", job.key().as_ref(), mint.key().as_ref()], bump)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProfile<'info> {
+    #[account(
+        init,
+        payer = wallet,
+        space = Profile::SIZE,
+        seeds = [b"profile", wallet.key().as_ref()],
+        bump,
+    )]
+    pub profile: Account<'info, Profile>,
+
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordLate<'info> {
+    #[account(mut)]
+    pub job: Account<'info, PaymentJob>,
+
+    #[account(mut, seeds = [b"profile", profile.wallet.as_ref()], bump)]
+    pub profile: Account<'info, Profile>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveDistribution<'info> {
+    #[account(mut)]
+    pub job: Account<'info, PaymentJob>,
+
+    pub approver: Signer<'info>,
+}
+
+// ==================== Account Structs ====================
+
+pub const MAX_SETTLEMENT_NOTE_LEN: usize = 64;
+pub const MAX_MEMO_LEN: usize = 64;
+pub const NETTED_SETTLEMENT_NOTE: &str = "Netted across jobs";
+
+#[account]
+pub struct PaymentJob {
+    pub authority: Pubkey, // Creator/Payee who controls the job
+    pub amount: u64, // Amount payer owes
+    pub remainder: u16, // Number of payers, first listed, owing one lamport more than `amount`
+    pub split_total: bool, // Whether `amount` was split from a total bill across the payers
+    pub deadline: i64, // Deadline for payment
+    pub closed: bool, // Job closed
+    pub late_fee: Option<LateFee>, // Fee charged on payments after the deadline
+    pub late_fees_collected: u64, // Late fees paid so far
+    pub require_acceptance: bool, // Payments open only once every payer accepted
+    pub allow_withdrawals: bool, // Paid payers may withdraw before their deadline
+    pub recurrence: Option<Recurrence>, // Set for jobs that repeat every period
+    pub escrow: Option<Escrow>, // Set for jobs that release funds only after delivery
+    pub milestones: Option<Milestones>, // Set for jobs that release funds in stages
+    pub approvals: Option<Approvals>, // Set for jobs that need M-of-N approval to distribute
+    pub vesting: Option<Vesting>, // Set for jobs that stream funds to payees over time
+    pub sponsorship: Option<Sponsorship>, // Set for jobs with a sponsor matching contributions
+    pub itemization: Option<Itemization>, // Set for jobs where payers owe the line items they claim
+    pub crank_bounty: u64, // Lamports paid to whoever distributes the job
+    pub token: Option<TokenConfig>, // Set for jobs paid in a token instead of SOL
+    pub accepted_assets: Vec<AcceptedAsset>, // Tokens payers may pay in instead of SOL
+    pub usd_pricing: Option<UsdPricing>, // Set for jobs whose amounts are in USD cents
+    pub parent: Option<Pubkey>, // Job this one pays into, as one of its payees
+    pub children: Vec<ChildJob>, // Jobs paying into this one
+    pub operators: Vec<Operator>, // Wallets granted roles by the authority
+    pub completion_hook: Option<CompletionHook>, // Program notified when the job is funded and distributed
+    pub payers: Vec<WalletStatus>, // Payers
+    pub payees: Vec<Pubkey>, // Payee
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct WalletStatus {
+    pub wallet: Pubkey, // The payer's wallet
+    pub paid: bool, // Whether the payer has paid
+    pub deadline: Option<i64>, // Individual deadline overriding the job deadline
+    pub settled_off_chain: bool, // Whether the authority recorded an off-chain settlement
+    pub settlement_note: Option<String>, // Optional note about the off-chain settlement
+    pub acceptance: Acceptance, // The payer's response to being listed
+    pub prepaid_periods: u16, // Upcoming periods already paid for
+    pub matched: u64, // Lamports the sponsor matched for this payer
+    pub memo: Option<String>, // Memo the payer left with their payment
+    pub late_recorded: bool, // Whether the missed deadline was recorded on the payer's profile
+    pub paid_late: bool, // Whether the payer paid after their deadline
+    pub paid_in: Option<u8>, // Accepted asset the payer paid in, None for SOL
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Acceptance {
+    Pending, // Payer has not responded yet
+    Accepted, // Payer agreed to the obligation
+    Declined, // Payer refused the obligation
+}
+
+pub const MAX_RECURRING_PAYERS: usize = 64;
+pub const MAX_PERIOD_HISTORY: usize = 12;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Recurrence {
+    pub period: i64, // Seconds between period deadlines
+    pub current_period: u32, // Index of the open period
+    pub history: Vec<PeriodRecord>, // Most recent finished periods, oldest first
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PeriodRecord {
+    pub period: u32, // Index of the finished period
+    pub paid_mask: u64, // Bit i is set when payer i paid on-chain
+    pub collected: u64, // Lamports collected for the period
+}
+
+impl Recurrence {
+    pub const SIZE: usize = 8 + 4 + 4 + MAX_PERIOD_HISTORY * (4 + 8 + 8);
+}
+
+pub const MAX_ESCROW_PAYERS: usize = 64;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Confirmer {
+    PayerMajority, // More than half of the paid payers confirm delivery
+    Arbiter, // The arbiter confirms delivery
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum EscrowState {
+    Pending, // Waiting for delivery confirmation
+    Confirmed, // Delivery confirmed, funds can be released
+    Disputed, // A payer disputed delivery
+    Resolved, // The arbiter split funds between payees and refunds
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Escrow {
+    pub confirmer: Confirmer, // Who confirms delivery
+    pub arbiter: Pubkey, // Resolves disputes
+    pub state: EscrowState, // Current escrow state
+    pub confirmations: u64, // Bit i is set when payer i confirmed delivery
+    pub refund_bps: u16, // Share of each contribution refunded after arbitration
+    pub refunded: u64, // Bit i is set once payer i claimed their refund
+}
+
+impl Escrow {
+    pub const SIZE: usize = 1 + 32 + 1 + 8 + 2 + 8;
+
+    pub fn is_released(&self) -> bool {
+        matches!(self.state, EscrowState::Confirmed | EscrowState::Resolved)
+    }
+
+    // Share of a payer's contribution refunded to them
+    pub fn refund_of(&self, contribution: u64) -> u64 {
+        (contribution as u128 * self.refund_bps as u128 / fees::BPS_DENOMINATOR as u128) as u64
+    }
+}
+
+pub const MAX_MILESTONES: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MilestoneConfig {
+    pub bps: u16, // Share of the collected funds released by this stage
+    pub unlock_time: Option<i64>, // Earliest time the stage can be released
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Milestone {
+    pub bps: u16, // Share of the collected funds released by this stage
+    pub unlock_time: Option<i64>, // Earliest time the stage can be released
+    pub released: bool, // Whether the stage was paid out
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Milestones {
+    pub approver: Option<Pubkey>, // May release stages besides the authority
+    pub stages: Vec<Milestone>, // Stages in order
+    pub total: u64, // Lamports collected when the first stage was released
+    pub released: u64, // Lamports released so far
+}
+
+impl Milestones {
+    pub fn space(num_stages: usize) -> usize {
+        (1 + 32) +
+        4 + num_stages * (2 + (1 + 8) + 1) +
+        8 +
+        8
+    }
+}
+
+pub const MAX_APPROVERS: usize = 10;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Approvals {
+    pub approvers: Vec<Pubkey>, // Wallets allowed to approve distribution
+    pub threshold: u8, // Approvals needed to distribute
+    pub approved: u32, // Bit i is set when approver i approved
+}
+
+impl Approvals {
+    pub fn space(num_approvers: usize) -> usize {
+        4 + num_approvers * 32 +
+        1 +
+        4
+    }
+
+    pub fn is_met(&self) -> bool {
+        self.approved.count_ones() >= self.threshold as u32
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Sponsorship {
+    pub sponsor: Pubkey, // Wallet that deposited the matching pool
+    pub ratio_bps: u16, // Match per payment, in basis points of the job amount
+    pub cap_per_payer: u64, // Most lamports matched for a single payer
+    pub pool: u64, // Lamports left to match with
+    pub matched: u64, // Lamports matched so far
+}
+
+impl Sponsorship {
+    pub const SIZE: usize = 32 + 2 + 8 + 8 + 8;
+
+    // Match owed for a payment of `amount`, limited by the cap and what is left
+    pub fn match_for(&self, amount: u64) -> u64 {
+        let ratio_match = (amount as u128 * self.ratio_bps as u128 / fees::BPS_DENOMINATOR as u128) as u64;
+        ratio_match.min(self.cap_per_payer).min(self.pool)
+    }
+}
+
+pub const MAX_LINE_ITEMS: usize = 16;
+pub const MAX_ITEM_DESCRIPTION_LEN: usize = 32;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LineItemConfig {
+    pub description: String, // What was bought
+    pub price: u64, // Lamports per unit
+    pub quantity: Option<u16>, // Units bought, 1 when not given
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LineItem {
+    pub description: String, // What was bought
+    pub price: u64, // Lamports per unit
+    pub quantity: u16, // Units bought
+    pub claims: Vec<u16>, // Basis points of the item claimed by each payer, in `payers` order
+}
+
+impl LineItem {
+    // Basis points of the item claimed so far
+    pub fn claimed_bps(&self) -> u64 {
+        self.claims.iter().map(|c| *c as u64).sum()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Itemization {
+    pub items: Vec<LineItem>, // Line items on the bill
+    pub tax_bps: u16, // Tax added to each payer's subtotal
+    pub tip_bps: u16, // Tip added to each payer's subtotal
+    pub collected: u64, // Lamports paid for claimed items, excluding late fees
+}
+
+impl Itemization {
+    pub fn space(num_items: usize, num_payers: usize) -> usize {
+        4 + num_items * ((4 + MAX_ITEM_DESCRIPTION_LEN) + 8 + 2 + 4 + num_payers * 2) +
+        2 +
+        2 +
+        8
+    }
+
+    // Claimed items plus tax and tip owed by the payer at `payer_index`, None on overflow
+    pub fn owed_by(&self, payer_index: usize) -> Option<u64> {
+        let subtotal = self.items.iter().try_fold(0u64, |sum, item| {
+            sum.checked_add(itemized::portion_cost(item.price, item.quantity, item.claims[payer_index])?)
+        })?;
+        itemized::with_surcharges(subtotal, self.tax_bps, self.tip_bps)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Vesting {
+    pub start: i64, // When funds start vesting
+    pub end: i64, // When funds are fully vested
+    pub total: u64, // Lamports collected when the first claim was made
+    pub claimed: Vec<u64>, // Lamports claimed by each payee, in `payees` order
+}
+
+impl Vesting {
+    pub fn space(num_payees: usize) -> usize {
+        8 +
+        8 +
+        8 +
+        4 + num_payees * 8
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct TokenConfig {
+    pub mint: Pubkey, // Mint payments are made in
+    pub token_program: Pubkey, // Legacy token program or Token-2022
+    pub vault_bump: u8, // Bump of the vault PDA holding the funds
+}
+
+impl TokenConfig {
+    pub const SIZE: usize = 32 + 32 + 1;
+}
+
+pub const MAX_ACCEPTED_ASSETS: usize = 4;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AcceptedAsset {
+    pub mint: Pubkey, // Token payers may pay in
+    pub token_program: Pubkey, // Legacy token program or Token-2022
+    pub vault_bump: u8, // Bump of the vault PDA holding this asset
+    pub numerator: u64, // Token base units per `denominator` lamports owed
+    pub denominator: u64, // Lamports the numerator is quoted against
+    pub collected: u64, // Token base units paid in so far
+    pub distributed: bool, // Whether the pool was paid out to payees
+}
+
+impl AcceptedAsset {
+    pub const SIZE: usize = 32 + 32 + 1 + 8 + 8 + 8 + 1;
+}
+
+pub const MAX_HOOK_ACCOUNTS: usize = 8;
+
+// Time after the distribution deadline during which distributing must notify the hook
+pub const HOOK_GRACE_PERIOD: i64 = 24 * 60 * 60;
+
+// Anchor discriminator of an `on_tab_completed` instruction: sha256("global:on_tab_completed")[..8]
+pub const COMPLETION_HOOK_DISCRIMINATOR: [u8; 8] = [76, 46, 231, 229, 66, 143, 137, 53];
+
+/// A program notified when a job completes.
+///
+/// The program is invoked with `COMPLETION_HOOK_DISCRIMINATOR` followed by the Borsh
+/// encoded `CompletionHookArgs`, which is what an Anchor instruction named
+/// `on_tab_completed(job: Pubkey, event: CompletionEvent, collected: u64)` expects.
+/// Its accounts are the job (read-only, already updated) followed by the registered
+/// accounts in order, none of them signers. It fires with `Funded` from the `pay`,
+/// `pay_in_asset` or `mark_settled` that resolves the last payer (not when the last
+/// one declines, is removed or is netted) and with `Distributed` whenever
+/// `distribute_funds` closes the job, even with nothing collected, once per period
+/// for recurring jobs. A failing hook fails the
+/// payment or distribution, except that `HOOK_GRACE_PERIOD` after the distribution
+/// deadline anyone distributing may skip the hook by leaving its program out, so a
+/// receiver can't hold the job's funds. Anyone can call the receiver directly, so
+/// receivers should check the top-level instruction belongs to this program (see
+/// `programs/tab_hook_receiver`).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CompletionHook {
+    pub program: Pubkey, // Program invoked on completion
+    pub accounts: Vec<HookAccount>, // Accounts passed after the job
+}
+
+impl CompletionHook {
+    pub fn space(num_accounts: usize) -> usize {
+        32 + 4 + num_accounts * HookAccount::SIZE
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct HookAccount {
+    pub pubkey: Pubkey,
+    pub is_writable: bool,
+}
+
+impl HookAccount {
+    pub const SIZE: usize = 32 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionEvent {
+    Funded, // Every payer paid or settled
+    Distributed, // Collected funds were paid out
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CompletionHookArgs {
+    pub job: Pubkey,
+    pub event: CompletionEvent,
+    pub collected: u64, // Lamports or base units collected for the period
+}
+
+pub const MAX_OPERATORS: usize = 8;
+
+// Roles the authority can grant to operators
+pub const ROLE_DISTRIBUTE: u8 = 1 << 0; // Distribute funds before the deadline
+pub const ROLE_EDIT_PAYERS: u8 = 1 << 1; // Add and remove payers, extend their deadlines
+pub const ROLE_MARK_SETTLED: u8 = 1 << 2; // Mark payers settled off-chain
+pub const ALL_ROLES: u8 = ROLE_DISTRIBUTE | ROLE_EDIT_PAYERS | ROLE_MARK_SETTLED;
+
+// Furthest past the job deadline an operator may push a payer's deadline
+pub const MAX_OPERATOR_EXTENSION: i64 = 7 * 24 * 60 * 60;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Operator {
+    pub wallet: Pubkey, // Wallet acting on the authority's behalf
+    pub roles: u8, // `ROLE_*` flags granted
+}
+
+impl Operator {
+    pub const SIZE: usize = 32 + 1;
+}
+
+pub const MAX_CHILD_JOBS: usize = 16;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ChildJob {
+    pub job: Pubkey, // Child job listing this job as a payee
+    pub settled: bool, // Whether the child was distributed
+    pub credited: u64, // Lamports the child paid into this job
+}
+
+impl ChildJob {
+    pub const SIZE: usize = 32 + 1 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct UsdConfig {
+    pub max_staleness: u32, // Seconds a price may be old when paying
+    pub max_confidence_bps: u16, // Widest confidence interval accepted, in bps of the price
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UsdPricing {
+    pub price_feed: Pubkey, // Pyth-format SOL/USD price account
+    pub max_staleness: u32, // Seconds a price may be old when paying
+    pub max_confidence_bps: u16, // Widest confidence interval accepted, in bps of the price
+    pub collected: u64, // Lamports paid in so far, excluding late fees
+}
+
+impl UsdPricing {
+    pub const SIZE: usize = 32 + 4 + 2 + 8;
+
+    // Read the feed's price, checking it is trading, fresh and confident enough
+    pub fn current_price(&self, price_feed: &AccountInfo, now: i64) -> Result<PriceFeed> {
+        let data = price_feed.try_borrow_data()?;
+        let price = oracle::parse_price_feed(&data).ok_or(error!(ErrorCode::InvalidPriceFeed))?;
+        price.validate(now, self.max_staleness, self.max_confidence_bps).map_err(|e| match e {
+            PriceError::Invalid => error!(ErrorCode::InvalidPriceFeed),
+            PriceError::Stale => error!(ErrorCode::StalePrice),
+            PriceError::Uncertain => error!(ErrorCode::PriceTooUncertain),
+        })?;
+        Ok(price)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LateFeeKind {
+    Flat, // Fixed lamports per late payment
+    Percentage, // Basis points of the job amount
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct LateFee {
+    pub kind: LateFeeKind, // How `value` is interpreted
+    pub value: u64, // Lamports or basis points
+    pub per_day: bool, // Charge the fee again for every started day late
+}
+
+impl LateFee {
+    pub const SIZE: usize = 1 + 8 + 1;
+
+    pub fn basis(&self) -> FeeBasis {
+        match self.kind {
+            LateFeeKind::Flat => FeeBasis::Flat(self.value),
+            LateFeeKind::Percentage => FeeBasis::Bps(self.value),
+        }
+    }
+}
+
+impl WalletStatus {
+    pub const SIZE: usize = 32 + 1 + (1 + 8) + 1 + (1 + 4 + MAX_SETTLEMENT_NOTE_LEN) + 1 + 2 + 8 + (1 + 4 + MAX_MEMO_LEN) + 1 + 1 + (1 + 1);
+
+    // Whether the payer no longer owes anything on this job
+    pub fn is_resolved(&self) -> bool {
+        self.paid || self.settled_off_chain || self.acceptance == Acceptance::Declined
+    }
+
+    // Individual deadlines only ever grant extra time over the job deadline
+    pub fn effective_deadline(&self, job_deadline: i64) -> i64 {
+        self.deadline.map_or(job_deadline, |d| d.max(job_deadline))
+    }
+}
+
+impl PaymentJob {
+    pub fn space(num_payers: usize, num_payees: usize) -> usize {
+        8 +
+        32 +
+        8 +
+        2 +
+        1 +
+        8 +
+        1 +
+        1 + LateFee::SIZE +
+        8 +
+        1 +
+        1 +
+        1 +
+        1 +
+        1 +
+        1 +
+        1 +
+        1 +
+        1 +
+        8 +
+        1 + TokenConfig::SIZE +
+        4 +
+        1 + UsdPricing::SIZE +
+        1 + 32 +
+        4 +
+        4 +
+        1 +
+        4 + num_payers * WalletStatus::SIZE +
+        4 + num_payees * 32  
+    }
+
+    // Records the finished period of a recurring job and reopens it for the next one,
+    // skipping whole periods whose deadline already passed by `now`
+    pub fn roll_over(&mut self, collected: u64, now: i64) -> Result<()> {
+        let Some(recurrence) = self.recurrence.as_mut() else {
+            return Ok(());
+        };
+
+        let paid_mask = self.payers.iter()
+            .enumerate()
+            .filter(|(_, p)| p.paid)
+            .fold(0u64, |mask, (i, _)| mask | 1 << i);
+        if recurrence.history.len() == MAX_PERIOD_HISTORY {
+            recurrence.history.remove(0);
+        }
+        recurrence.history.push(PeriodRecord { period: recurrence.current_period, paid_mask, collected });
+        recurrence.current_period += 1;
+        let missed = now.saturating_sub(self.deadline).max(0) / recurrence.period;
+        self.deadline = missed.checked_add(1)
+            .and_then(|periods| periods.checked_mul(recurrence.period))
+            .and_then(|advance| self.deadline.checked_add(advance))
+            .ok_or(error!(ErrorCode::MathOverflow))?;
+
+        // Prepaid periods count as paid as soon as the period starts
+        for ws in self.payers.iter_mut() {
+            ws.paid = ws.prepaid_periods > 0;
+            ws.prepaid_periods = ws.prepaid_periods.saturating_sub(1);
+            ws.deadline = None;
+            ws.settled_off_chain = false;
+            ws.settlement_note = None;
+            ws.memo = None;
+            ws.late_recorded = false;
+            ws.paid_late = false;
+            ws.paid_in = None;
+        }
+        self.late_fees_collected = 0;
+        self.closed = false;
+
+        // Every period's distribution is approved separately
+        if let Some(approvals) = self.approvals.as_mut() {
+            approvals.approved = 0;
+        }
+
+        msg!("Period {} started, next deadline {}", recurrence.current_period, self.deadline);
+        Ok(())
+    }
+
+    // Lamports paid into the job for payees: contributions, late fees and sponsor matches
+    pub fn collected(&self) -> u64 {
+        let contributions = match (&self.itemization, &self.usd_pricing) {
+            (Some(itemization), _) => itemization.collected,
+            (None, Some(usd)) => usd.collected,
+            (None, None) => self.payers.iter()
+                .enumerate()
+                .filter(|(_, p)| p.paid && p.paid_in.is_none())
+                .map(|(i, _)| self.share_of(i))
+                .sum(),
+        };
+        let matched = self.sponsorship.as_ref().map_or(0, |s| s.matched);
+        contributions + self.late_fees_collected + matched + self.children_credited()
+    }
+
+    // Lamports paid in by child jobs
+    pub fn children_credited(&self) -> u64 {
+        self.children.iter().map(|c| c.credited).sum()
+    }
+
+    // Records that `child` was distributed, paying `amount` into this job
+    pub fn credit_child(&mut self, child: Pubkey, amount: u64) -> Result<()> {
+        let entry = self.children.iter_mut()
+            .find(|c| c.job == child)
+            .ok_or(error!(ErrorCode::NotChildJob))?;
+        require!(!entry.settled, ErrorCode::AlreadyClosed);
+        require!(!self.closed, ErrorCode::AlreadyClosed);
+        entry.settled = true;
+        entry.credited = amount;
+        Ok(())
+    }
+
+    // Amount the payer at `payer_index` owes, before any late fee
+    pub fn amount_owed(&self, payer_index: usize) -> Result<u64> {
+        match &self.itemization {
+            Some(itemization) => itemization.owed_by(payer_index).ok_or(error!(ErrorCode::MathOverflow)),
+            None => Ok(self.share_of(payer_index)),
+        }
+    }
+
+    // Payers who paid or still owe, leaving out those who declined or settled off-chain
+    pub fn contributing_payers(&self) -> usize {
+        self.payers.iter().filter(|p| p.paid || !p.is_resolved()).count()
+    }
+
+    // Refunds the arbiter granted to paid payers, claimed or not
+    pub fn escrow_refunds(&self) -> Result<u64> {
+        let Some(escrow) = &self.escrow else {
+            return Ok(0);
+        };
+        let mut refunds = 0u64;
+        for (i, _) in self.payers.iter().enumerate().filter(|(_, p)| p.paid) {
+            refunds = refunds
+                .checked_add(escrow.refund_of(self.amount_owed(i)?))
+                .ok_or(error!(ErrorCode::MathOverflow))?;
+        }
+        Ok(refunds)
+    }
+
+    // Even share of the bill owed by the payer at `payer_index`
+    pub fn share_of(&self, payer_index: usize) -> u64 {
+        self.amount + u64::from(payer_index < self.remainder as usize)
+    }
+
+    // Whether `signer` is the authority or an operator holding `role`
+    pub fn is_authorized(&self, signer: &Pubkey, role: u8) -> bool {
+        *signer == self.authority || self.operators.iter().any(|o| o.wallet == *signer && o.roles & role != 0)
+    }
+
+    // Whether enough approvers signed off on distributing the funds
+    pub fn distribution_approved(&self) -> bool {
+        match &self.approvals {
+            Some(approvals) => approvals.is_met(),
+            None => true,
+        }
+    }
+
+    // Whether payers may pay into the job, which waits until nobody is left to respond
+    pub fn payments_open(&self) -> bool {
+        !self.require_acceptance || self.payers.iter().all(|p| p.acceptance != Acceptance::Pending)
+    }
+
+    // Late fee owed by the payer at `payer_index` if paying at `now`
+    pub fn late_fee_for(&self, payer_index: usize, now: i64) -> Result<u64> {
+        let Some(late_fee) = self.late_fee else {
+            return Ok(0);
+        };
+        let deadline = self.payers[payer_index].effective_deadline(self.deadline);
+        fees::late_fee(late_fee.basis(), late_fee.per_day, self.amount_owed(payer_index)?, deadline, now)
+            .ok_or(error!(ErrorCode::MathOverflow))
+    }
+
+    // Latest deadline among payers who still have time to pay
+    pub fn distribution_deadline(&self) -> i64 {
+        self.payers.iter()
+            .filter(|p| !p.is_resolved())
+            .map(|p| p.effective_deadline(self.deadline))
+            .fold(self.deadline, i64::max)
+    }
+}
+
+pub const MAX_TITLE_LEN: usize = 32;
+pub const MAX_DESCRIPTION_LEN: usize = 200;
+pub const MAX_CATEGORY_LEN: usize = 16;
+pub const MAX_REFERENCE_LEN: usize = 32;
+
+#[account]
+pub struct JobMetadata {
+    pub job: Pubkey, // Job this metadata describes
+    pub title: String, // Short human readable name
+    pub description: String, // What the job is for
+    pub category: String, // Free-form category, e.g. "dinner" or "rent"
+    pub reference: String, // External invoice or reference id
+}
+
+impl JobMetadata {
+    pub const SIZE: usize = 8 +
+        32 +
+        4 + MAX_TITLE_LEN +
+        4 + MAX_DESCRIPTION_LEN +
+        4 + MAX_CATEGORY_LEN +
+        4 + MAX_REFERENCE_LEN;
+}
+
+#[account]
+pub struct Profile {
+    pub wallet: Pubkey, // Wallet whose payments are tracked
+    pub jobs_paid: u64, // Payments made into jobs
+    pub on_time: u64, // Payments made by the deadline
+    pub late: u64, // Deadlines missed, whether paid late or not at all
+    pub total_paid: u64, // Lamports paid into jobs
+}
+
+impl Profile {
+    pub const SIZE: usize = 8 + 32 + 8 + 8 + 8 + 8;
+
+    // Records a payment of `amount`; a late payment whose missed deadline was already
+    // recorded is not counted as late again
+    pub fn record_payment(&mut self, amount: u64, late: bool, late_recorded: bool) -> Result<()> {
+        self.jobs_paid += 1;
+        self.total_paid = self.total_paid
+            .checked_add(amount)
+            .ok_or(error!(ErrorCode::MathOverflow))?;
+        if !late {
+            self.on_time += 1;
+        } else if !late_recorded {
+            self.late += 1;
+        }
+        Ok(())
+    }
+}
+
+pub const MAX_GROUP_MEMBERS: usize = 32;
+
+#[account]
+pub struct Group {
+    pub authority: Pubkey, // Creator of the group
+    pub members: Vec<GroupMember>, // Members and their running balances
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GroupMember {
+    pub wallet: Pubkey, // The member's wallet
+    pub balance: i64, // Net lamports owed to (positive) or by (negative) the member
+}
+
+impl Group {
+    pub fn space(num_members: usize) -> usize {
+        8 +
+        32 +
+        4 + num_members * (32 + 8)
+    }
+
+    pub fn member_index(&self, wallet: &Pubkey) -> Option<usize> {
+        self.members.iter().position(|m| m.wallet == *wallet)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct NettedTransfer {
+    pub from: Pubkey, // Wallet sending lamports, must sign
+    pub to: Pubkey, // Wallet receiving lamports
+    pub amount: u64, // Lamports to send
+}
+
+// ==================== Events ====================
+
+#[event]
+pub struct PaymentMade {
+    pub job: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub memo: Option<String>,
+}
+
+#[event]
+pub struct PledgeWithdrawn {
+    pub job: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Invalid input parameters")]
+    InvalidInput,
+    #[msg("Contributor has already paid")]
+    AlreadyPaid,
+    #[msg("Contributor is not listed")]
+    NotContributor,
+    #[msg("Payment job is already closed")]
+    AlreadyClosed,
+    #[msg("Cannot distribute before deadline unless authority")]
+    BeforeDeadline,
+    #[msg("Insufficient funds in job account")]
+    InsufficientFunds,
+    #[msg("Only the job authority can perform this action")]
+    Unauthorized,
+    #[msg("New deadline must be later than the current one")]
+    DeadlineNotExtended,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Settlement note is too long")]
+    NoteTooLong,
+    #[msg("Payments open once every payer has responded")]
+    PaymentsNotOpen,
+    #[msg("Payer declined this payment job")]
+    Declined,
+    #[msg("Payment job does not allow withdrawals")]
+    WithdrawalsNotAllowed,
+    #[msg("Contributor has not paid")]
+    NotPaid,
+    #[msg("Deadline has passed")]
+    AfterDeadline,
+    #[msg("Wallet is not a group member")]
+    NotMember,
+    #[msg("Settlement exceeds the outstanding balance")]
+    SettlementTooLarge,
+    #[msg("Transfer plan does not settle the jobs exactly")]
+    InvalidNettingPlan,
+    #[msg("Required account was not provided")]
+    MissingAccount,
+    #[msg("Wallet must sign")]
+    MissingSigner,
+    #[msg("Payment job is not recurring")]
+    NotRecurring,
+    #[msg("Payment job is not an escrow")]
+    NotEscrow,
+    #[msg("Escrow is not in the right state for this action")]
+    InvalidEscrowState,
+    #[msg("Escrow funds have not been released")]
+    EscrowNotReleased,
+    #[msg("Refund already claimed")]
+    AlreadyRefunded,
+    #[msg("Payment job does not release funds in milestones")]
+    NotStaged,
+    #[msg("Payment job releases funds through milestones")]
+    UseMilestones,
+    #[msg("Milestone already released")]
+    MilestoneReleased,
+    #[msg("Milestone is not unlocked yet")]
+    MilestoneLocked,
+    #[msg("Distribution has not been approved by enough approvers")]
+    NotApproved,
+    #[msg("Payment job has no approvers")]
+    NoApprovers,
+    #[msg("Approver already approved")]
+    AlreadyApproved,
+    #[msg("Payment job streams funds through vesting")]
+    UseVesting,
+    #[msg("Payment job does not vest funds")]
+    NotVesting,
+    #[msg("Wallet is not a payee of this job")]
+    NotPayee,
+    #[msg("Nothing to claim yet")]
+    NothingToClaim,
+    #[msg("Metadata field is too long")]
+    MetadataTooLong,
+    #[msg("Payment job is not itemized")]
+    NotItemized,
+    #[msg("Item is already fully claimed")]
+    ItemOverclaimed,
+    #[msg("Nothing claimed to pay for")]
+    NothingClaimed,
+    #[msg("Payment memo is too long")]
+    MemoTooLong,
+    #[msg("Missed deadline was already recorded")]
+    AlreadyRecorded,
+    #[msg("Mint has an extension token jobs do not support")]
+    UnsupportedMint,
+    #[msg("Token accounts do not match the job's mint")]
+    WrongMint,
+    #[msg("Not supported for token jobs")]
+    UnsupportedForTokens,
+    #[msg("Payment job has not been distributed yet")]
+    NotDistributed,
+    #[msg("Not supported for USD-denominated jobs")]
+    UnsupportedForUsd,
+    #[msg("Price feed does not match the job")]
+    WrongPriceFeed,
+    #[msg("Account is not a usable Pyth price feed")]
+    InvalidPriceFeed,
+    #[msg("Price feed is stale")]
+    StalePrice,
+    #[msg("Price confidence interval is too wide")]
+    PriceTooUncertain,
+    #[msg("Child jobs have not all been distributed")]
+    ChildrenPending,
+    #[msg("Job is not a child of this job")]
+    NotChildJob,
+    #[msg("Completion hook accounts do not match the job")]
+    WrongHookAccounts,
+    #[msg("Payments are open, responses can no longer change")]
+    ResponsesClosed,
+    #[msg("Payment job cannot be settled by netting")]
+    UnsupportedForNetting,
+    #[msg("Profiles do not track jobs that allow withdrawals")]
+    WithdrawalsAllowed,
+    #[msg("Operators can extend a payer's deadline at most a week past the job deadline")]
+    ExtensionTooLong,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::entrypoint::ProgramResult;
+    use anchor_lang::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+    use anchor_lang::solana_program::system_program;
+    use anchor_lang::Discriminator;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token;
+    use std::cell::{Cell, RefCell};
+    use std::sync::Once;
+
+    const DAY: i64 = 86_400;
+
+    thread_local! {
+        static NOW: Cell<i64> = const { Cell::new(0) };
+        static EVENTS: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+        static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Serves the clock from `NOW`, collects emitted events in `EVENTS` and carries out
+    // system transfers between the passed accounts, recording other CPIs in `INVOKED`
+    struct TestRuntime;
+
+    impl SyscallStubs for TestRuntime {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock { unix_timestamp: NOW.with(Cell::get), ..Clock::default() };
+            unsafe { *(var_addr as *mut Clock) = clock };
+            0
+        }
+
+        fn sol_log_data(&self, fields: &[&[u8]]) {
+            EVENTS.with(|events| events.borrow_mut().extend(fields.iter().map(|f| f.to_vec())));
+        }
+
+        fn sol_invoke_signed(&self, instruction: &Instruction, account_infos: &[AccountInfo], _signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+            if instruction.program_id == system_program::ID && instruction.data[..4] == 2u32.to_le_bytes() {
+                let amount = u64::from_le_bytes(instruction.data[4..12].try_into().unwrap());
+                let find = |key: &Pubkey| account_infos.iter().find(|a| a.key == key).unwrap();
+                let (from, to) = (find(&instruction.accounts[0].pubkey), find(&instruction.accounts[1].pubkey));
+                **from.try_borrow_mut_lamports()? -= amount;
+                **to.try_borrow_mut_lamports()? += amount;
+            } else if instruction.program_id != system_program::ID {
+                INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+            }
+            Ok(())
+        }
+    }
+
+    fn at(now: i64) {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(TestRuntime));
+        });
+        NOW.with(|n| n.set(now));
+    }
+
+    fn account(key: Pubkey, owner: Pubkey, lamports: u64, data: Vec<u8>, is_signer: bool) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(lamports)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        )
+    }
+
+    fn wallet(key: Pubkey, is_signer: bool) -> AccountInfo<'static> {
+        account(key, system_program::ID, 0, Vec::new(), is_signer)
+    }
+
+    fn signer(key: Pubkey, lamports: u64) -> Signer<'static> {
+        let info = Box::leak(Box::new(account(key, system_program::ID, lamports, Vec::new(), true)));
+        Signer::try_from(&*info).unwrap()
+    }
+
+    fn system() -> Program<'static, System> {
+        let mut info = account(system_program::ID, Pubkey::default(), 1, Vec::new(), false);
+        info.executable = true;
+        Program::try_from(&*Box::leak(Box::new(info))).unwrap()
+    }
+
+    fn job_account(job: &PaymentJob, lamports: u64) -> Account<'static, PaymentJob> {
+        let mut data = PaymentJob::DISCRIMINATOR.to_vec();
+        job.serialize(&mut data).unwrap();
+        // Leave the room create_payment_job allocates for notes and memos
+        data.resize(data.len().max(PaymentJob::space(job.payers.len(), job.payees.len())), 0);
+        let info = Box::leak(Box::new(account(Pubkey::new_unique(), crate::ID, lamports, data, false)));
+        Account::try_from(info).unwrap()
+    }
+
+    fn new_job(authority: Pubkey, payers: &[Pubkey], payees: &[Pubkey], amount: u64, deadline: i64) -> PaymentJob {
+        PaymentJob {
+            authority,
+            amount,
+            remainder: 0,
+            split_total: false,
+            deadline,
+            closed: false,
+            late_fee: None,
+            late_fees_collected: 0,
+            require_acceptance: false,
+            allow_withdrawals: false,
+            recurrence: None,
+            escrow: None,
+            milestones: None,
+            approvals: None,
+            vesting: None,
+            sponsorship: None,
+            itemization: None,
+            crank_bounty: 0,
+            token: None,
+            accepted_assets: Vec::new(),
+            usd_pricing: None,
+            parent: None,
+            children: Vec::new(),
+            operators: Vec::new(),
+            completion_hook: None,
+            payers: payers.iter().map(|wallet| WalletStatus {
+                wallet: *wallet,
+                paid: false,
+                deadline: None,
+                settled_off_chain: false,
+                settlement_note: None,
+                acceptance: Acceptance::Pending,
+                prepaid_periods: 0,
+                matched: 0,
+                memo: None,
+                late_recorded: false,
+                paid_late: false,
+                paid_in: None,
+            }).collect(),
+            payees: payees.to_vec(),
+        }
+    }
+
+    fn assert_error<T>(result: Result<T>, code: ErrorCode) {
+        match result {
+            Err(Error::AnchorError(e)) => assert_eq!(e.error_code_number, u32::from(code)),
+            Err(e) => panic!("unexpected error {e:?}"),
+            Ok(_) => panic!("expected {code:?}"),
+        }
+    }
+
+    // Job where one of two payers paid 1 SOL into two milestones, half each
+    fn staged_job(authority: Pubkey, approver: Pubkey, payees: &[Pubkey]) -> PaymentJob {
+        let mut job = new_job(authority, &[Pubkey::new_unique(), Pubkey::new_unique()], payees, 1_000_000_000, 10 * DAY);
+        job.payers[0].paid = true;
+        job.milestones = Some(Milestones {
+            approver: Some(approver),
+            stages: vec![
+                Milestone { bps: 5_000, unlock_time: None, released: false },
+                Milestone { bps: 5_000, unlock_time: None, released: false },
+            ],
+            total: 0,
+            released: 0,
+        });
+        job
+    }
+
+    fn release(job: &mut Account<'static, PaymentJob>, signer: Pubkey, payees: &[AccountInfo<'static>], index: u8) -> Result<()> {
+        let mut accounts = ReleaseMilestone { job: job.clone(), signer: self::signer(signer, 0) };
+        let payees = Box::leak(payees.to_vec().into_boxed_slice());
+        let result = crate::tab_pool::release_milestone(Context::new(&crate::ID, &mut accounts, payees, Default::default()), index);
+        *job = accounts.job;
+        result
+    }
+
+    #[test]
+    fn first_milestone_waits_for_the_deadline() {
+        let (authority, approver) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let payee = wallet(Pubkey::new_unique(), false);
+        let mut job = job_account(&staged_job(authority, approver, &[*payee.key]), 2_000_000_000);
+        let payees = [payee.clone()];
+
+        at(5 * DAY);
+        assert_error(release(&mut job, approver, &payees, 0), ErrorCode::BeforeDeadline);
+        assert!(!job.closed);
+
+        at(11 * DAY);
+        release(&mut job, approver, &payees, 0).unwrap();
+        assert!(job.closed);
+        assert_eq!(payee.lamports(), 500_000_000);
+    }
+
+    #[test]
+    fn authority_may_release_early() {
+        let authority = Pubkey::new_unique();
+        let payee = wallet(Pubkey::new_unique(), false);
+        let mut job = job_account(&staged_job(authority, Pubkey::new_unique(), &[*payee.key]), 2_000_000_000);
+
+        at(5 * DAY);
+        release(&mut job, authority, std::slice::from_ref(&payee), 1).unwrap();
+        assert_eq!(job.milestones.as_ref().unwrap().total, 1_000_000_000);
+        assert_eq!(payee.lamports(), 500_000_000);
+    }
+
+    fn add_sponsor(job: &mut Account<'static, PaymentJob>, sponsor: &Signer<'static>, deposit: u64) -> Result<()> {
+        let mut accounts = AddSponsor {
+            job: job.clone(),
+            authority: signer(job.authority, 0),
+            sponsor: sponsor.clone(),
+            system_program: system(),
+        };
+        let result = crate::tab_pool::add_sponsor(Context::new(&crate::ID, &mut accounts, &[], Default::default()), 5_000, 1_000, deposit);
+        *job = accounts.job;
+        result
+    }
+
+    #[test]
+    fn sponsor_must_join_before_anyone_pays() {
+        at(0);
+        let sponsor = signer(Pubkey::new_unique(), 10_000);
+        let mut job = new_job(Pubkey::new_unique(), &[Pubkey::new_unique(), Pubkey::new_unique()], &[Pubkey::new_unique()], 1_000, DAY);
+        job.payers[1].paid = true;
+        let mut paid = job_account(&job, 1_000);
+        assert_error(add_sponsor(&mut paid, &sponsor, 5_000), ErrorCode::AlreadyPaid);
+        assert!(paid.sponsorship.is_none());
+
+        job.payers[1].paid = false;
+        let mut unpaid = job_account(&job, 0);
+        add_sponsor(&mut unpaid, &sponsor, 5_000).unwrap();
+        assert_eq!(unpaid.sponsorship.as_ref().unwrap().pool, 5_000);
+        assert_eq!(unpaid.to_account_info().lamports(), 5_000);
+        assert_eq!(sponsor.lamports(), 5_000);
+    }
+
+    fn distribute(job: &mut Account<'static, PaymentJob>, caller: &'static AccountInfo<'static>) -> Result<()> {
+        distribute_with_hook(job, caller, None)
+    }
+
+    fn distribute_with_hook(job: &mut Account<'static, PaymentJob>, caller: &'static AccountInfo<'static>, hook_program: Option<Pubkey>) -> Result<()> {
+        let mut accounts = DistributeFunds {
+            job: job.clone(),
+            authority: UncheckedAccount::try_from(caller),
+            sponsor: None,
+            vault: None,
+            mint: None,
+            token_program: None,
+            parent: None,
+            hook_program: hook_program.map(hook),
+            system_program: system(),
+        };
+        let result = crate::tab_pool::distribute_funds(Context::new(&crate::ID, &mut accounts, &[], Default::default()));
+        *job = accounts.job;
+        result
+    }
+
+    // Recurring job nobody paid into, carrying a 5_000 lamport bounty
+    fn bountied_job(authority: Pubkey) -> Account<'static, PaymentJob> {
+        let mut job = new_job(authority, &[Pubkey::new_unique()], &[Pubkey::new_unique()], 1_000, DAY);
+        job.crank_bounty = 5_000;
+        job.recurrence = Some(Recurrence { period: DAY, current_period: 0, history: Vec::new() });
+        job_account(&job, 5_000)
+    }
+
+    #[test]
+    fn crank_bounty_is_paid_once_when_closing_an_unpaid_job() {
+        let mut job = bountied_job(Pubkey::new_unique());
+        let cranker: &'static AccountInfo<'static> = Box::leak(Box::new(wallet(Pubkey::new_unique(), true)));
+
+        at(DAY);
+        distribute(&mut job, cranker).unwrap();
+        assert_eq!(cranker.lamports(), 5_000);
+        assert_eq!(job.crank_bounty, 0);
+        assert_eq!(job.to_account_info().lamports(), 0);
+
+        // The next period closes too, without paying again
+        at(2 * DAY);
+        distribute(&mut job, cranker).unwrap();
+        assert_eq!(cranker.lamports(), 5_000);
+        assert_eq!(job.recurrence.as_ref().unwrap().current_period, 2);
+    }
+
+    #[test]
+    fn authority_distributing_early_gets_the_bounty_back() {
+        let authority = Pubkey::new_unique();
+        let mut job = bountied_job(authority);
+        let operator = Pubkey::new_unique();
+        job.operators.push(Operator { wallet: operator, roles: ROLE_DISTRIBUTE });
+        let operator: &'static AccountInfo<'static> = Box::leak(Box::new(wallet(operator, true)));
+        let authority: &'static AccountInfo<'static> = Box::leak(Box::new(wallet(authority, true)));
+
+        at(DAY / 2);
+        assert_error(distribute(&mut job, operator), ErrorCode::Unauthorized);
+        distribute(&mut job, authority).unwrap();
+        assert_eq!(authority.lamports(), 5_000);
+        assert_eq!(job.crank_bounty, 0);
+    }
+
+    fn pay(job: &mut Account<'static, PaymentJob>, payer: &Signer<'static>) -> Result<()> {
+        pay_at_price(job, payer, None)
+    }
+
+    fn pay_at_price(job: &mut Account<'static, PaymentJob>, payer: &Signer<'static>, price_feed: Option<&'static AccountInfo<'static>>) -> Result<()> {
+        let mut accounts = Pay {
+            job: job.clone(),
+            payer: payer.clone(),
+            profile: None,
+            payer_token_account: None,
+            vault: None,
+            mint: None,
+            token_program: None,
+            price_feed: price_feed.map(UncheckedAccount::try_from),
+            hook_program: None,
+            system_program: system(),
+        };
+        let result = crate::tab_pool::pay(Context::new(&crate::ID, &mut accounts, &[], Default::default()), None, false);
+        *job = accounts.job;
+        result
+    }
+
+    fn set_items(job: &mut Account<'static, PaymentJob>, items: Vec<LineItemConfig>, tax_bps: u16) -> Result<()> {
+        let mut accounts = SetItems { job: job.clone(), authority: signer(job.authority, 0), system_program: system() };
+        let result = crate::tab_pool::set_items(Context::new(&crate::ID, &mut accounts, &[], Default::default()), items, tax_bps, 0);
+        *job = accounts.job;
+        result
+    }
+
+    fn item(description: &str, price: u64, quantity: Option<u16>) -> LineItemConfig {
+        LineItemConfig { description: description.to_string(), price, quantity }
+    }
+
+    #[test]
+    fn only_sol_jobs_owing_per_payer_are_itemized() {
+        at(0);
+        let job = new_job(Pubkey::new_unique(), &[Pubkey::new_unique()], &[Pubkey::new_unique()], 1_000, DAY);
+
+        let mut split = job.clone();
+        split.split_total = true;
+        assert_error(set_items(&mut job_account(&split, 0), vec![item("pizza", 1_000, None)], 0), ErrorCode::InvalidInput);
+
+        let mut token = job.clone();
+        token.token = Some(TokenConfig { mint: Pubkey::new_unique(), token_program: Pubkey::new_unique(), vault_bump: 255 });
+        assert_error(set_items(&mut job_account(&token, 0), vec![item("pizza", 1_000, None)], 0), ErrorCode::UnsupportedForTokens);
+
+        let mut sol = job_account(&job, 0);
+        set_items(&mut sol, vec![item("pizza", 1_000, None)], 0).unwrap();
+        assert_eq!(sol.itemization.as_ref().unwrap().items.len(), 1);
+    }
+
+    #[test]
+    fn itemized_payers_pay_for_their_claims() {
+        at(0);
+        let payer = signer(Pubkey::new_unique(), 10_000);
+        let mut job = job_account(&new_job(Pubkey::new_unique(), &[payer.key(), Pubkey::new_unique()], &[Pubkey::new_unique()], 1_000, DAY), 0);
+        set_items(&mut job, vec![item("pizza", 1_500, Some(2)), item("drinks", 1_000, None)], 1_000).unwrap();
+
+        let mut accounts = ClaimItem { job: job.clone(), payer: payer.clone() };
+        crate::tab_pool::claim_item(Context::new(&crate::ID, &mut accounts, &[], Default::default()), 0, 5_000).unwrap();
+        crate::tab_pool::claim_item(Context::new(&crate::ID, &mut accounts, &[], Default::default()), 1, 10_000).unwrap();
+        job = accounts.job;
+
+        // Half of two pizzas and all the drinks, plus 10% tax
+        pay(&mut job, &payer).unwrap();
+        assert_eq!(payer.lamports(), 10_000 - 2_750);
+        assert_eq!(job.to_account_info().lamports(), 2_750);
+        assert_eq!(job.itemization.as_ref().unwrap().collected, 2_750);
+        assert!(job.payers[0].paid);
+    }
+
+    fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> InterfaceAccount<'static, TokenAccount> {
+        let state = spl_token::state::Account { mint, owner, amount, state: spl_token::state::AccountState::Initialized, ..Default::default() };
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(state, &mut data).unwrap();
+        InterfaceAccount::try_from(&*Box::leak(Box::new(account(Pubkey::new_unique(), spl_token::ID, 1, data, false)))).unwrap()
+    }
+
+    fn mint(key: Pubkey) -> InterfaceAccount<'static, Mint> {
+        let state = spl_token::state::Mint { decimals: 6, is_initialized: true, ..Default::default() };
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(state, &mut data).unwrap();
+        InterfaceAccount::try_from(&*Box::leak(Box::new(account(key, spl_token::ID, 1, data, false)))).unwrap()
+    }
+
+    fn token_program() -> Interface<'static, TokenInterface> {
+        let mut info = account(spl_token::ID, Pubkey::default(), 1, Vec::new(), false);
+        info.executable = true;
+        Interface::try_from(&*Box::leak(Box::new(info))).unwrap()
+    }
+
+    fn profile(wallet: Pubkey) -> Account<'static, Profile> {
+        let mut data = Profile::DISCRIMINATOR.to_vec();
+        Profile { wallet, jobs_paid: 0, on_time: 0, late: 0, total_paid: 0 }.serialize(&mut data).unwrap();
+        Account::try_from(&*Box::leak(Box::new(account(Pubkey::new_unique(), crate::ID, 1, data, false)))).unwrap()
+    }
+
+    #[test]
+    fn paying_in_an_asset_counts_on_the_profile() {
+        at(2 * DAY);
+        let payer = signer(Pubkey::new_unique(), 0);
+        let usdc = Pubkey::new_unique();
+        let mut job = new_job(Pubkey::new_unique(), &[payer.key(), Pubkey::new_unique()], &[Pubkey::new_unique()], 1_000, DAY);
+        job.accepted_assets.push(AcceptedAsset {
+            mint: usdc,
+            token_program: spl_token::ID,
+            vault_bump: 255,
+            numerator: 3,
+            denominator: 2,
+            collected: 0,
+            distributed: false,
+        });
+        let job = job_account(&job, 0);
+
+        let mut accounts = PayInAsset {
+            job: job.clone(),
+            payer: payer.clone(),
+            profile: Some(profile(payer.key())),
+            payer_token_account: token_account(usdc, payer.key(), 10_000),
+            vault: token_account(usdc, job.key(), 0),
+            mint: mint(usdc),
+            token_program: token_program(),
+            hook_program: None,
+        };
+        crate::tab_pool::pay_in_asset(Context::new(&crate::ID, &mut accounts, &[], Default::default()), 0).unwrap();
+
+        assert_eq!(accounts.job.accepted_assets[0].collected, 1_500);
+        assert!(accounts.job.payers[0].paid_late && accounts.job.payers[0].late_recorded);
+        let profile = accounts.profile.as_ref().unwrap();
+        assert_eq!((profile.jobs_paid, profile.late, profile.on_time), (1, 1, 0));
+        assert_eq!(profile.total_paid, 1_000);
+
+        let events = EVENTS.with(|events| events.take());
+        let event = events.iter().find(|e| e.starts_with(&PaymentMade::DISCRIMINATOR)).unwrap();
+        let event = PaymentMade::try_from_slice(&event[8..]).unwrap();
+        assert_eq!((event.payer, event.amount), (payer.key(), 1_000));
+    }
+
+    fn add_child(parent: &mut Account<'static, PaymentJob>, child: &mut Account<'static, PaymentJob>) -> Result<()> {
+        let mut accounts = AddChildJob { parent: parent.clone(), child: child.clone(), authority: signer(parent.authority, 0), system_program: system() };
+        let result = crate::tab_pool::add_child_job(Context::new(&crate::ID, &mut accounts, &[], Default::default()));
+        *parent = accounts.parent;
+        *child = accounts.child;
+        result
+    }
+
+    #[test]
+    fn child_jobs_nest_one_level_deep() {
+        at(0);
+        let authority = Pubkey::new_unique();
+        let mut a = job_account(&new_job(authority, &[Pubkey::new_unique()], &[Pubkey::new_unique()], 1_000, DAY), 0);
+        let mut b = job_account(&new_job(authority, &[Pubkey::new_unique()], &[a.key()], 1_000, DAY), 0);
+        add_child(&mut a, &mut b).unwrap();
+        assert_eq!(b.parent, Some(a.key()));
+
+        // The parent can't be made a child of its own child, nor of any other job
+        a.payees.push(b.key());
+        assert_error(add_child(&mut b, &mut a), ErrorCode::InvalidInput);
+        let mut c = job_account(&new_job(authority, &[Pubkey::new_unique()], &[Pubkey::new_unique()], 1_000, DAY), 0);
+        assert_error(add_child(&mut c, &mut a), ErrorCode::InvalidInput);
+
+        // Nor can the child take children of its own
+        let mut d = job_account(&new_job(authority, &[Pubkey::new_unique()], &[b.key()], 1_000, DAY), 0);
+        assert_error(add_child(&mut b, &mut d), ErrorCode::InvalidInput);
+        assert!(a.parent.is_none() && b.children.is_empty() && d.parent.is_none());
+    }
+
+    fn extend(job: &mut Account<'static, PaymentJob>, signer: Pubkey, new_deadline: i64, payer: Option<Pubkey>) -> Result<()> {
+        let mut accounts = ExtendDeadline { job: job.clone(), authority: self::signer(signer, 0) };
+        let result = crate::tab_pool::extend_deadline(Context::new(&crate::ID, &mut accounts, &[], Default::default()), new_deadline, payer);
+        *job = accounts.job;
+        result
+    }
+
+    #[test]
+    fn operators_extend_payer_deadlines_within_limits() {
+        at(0);
+        let (authority, operator, payer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut job = new_job(authority, &[payer], &[Pubkey::new_unique()], 1_000, DAY);
+        job.operators.push(Operator { wallet: operator, roles: ROLE_EDIT_PAYERS });
+        let mut job = job_account(&job, 0);
+
+        let limit = DAY + MAX_OPERATOR_EXTENSION;
+        assert_error(extend(&mut job, operator, limit + 1, Some(payer)), ErrorCode::ExtensionTooLong);
+        extend(&mut job, operator, limit, Some(payer)).unwrap();
+        assert_eq!(job.payers[0].deadline, Some(limit));
+
+        // Repeated extensions can't creep past the limit, but the authority isn't bound by it
+        assert_error(extend(&mut job, operator, limit + DAY, Some(payer)), ErrorCode::ExtensionTooLong);
+        assert_error(extend(&mut job, operator, 10 * DAY, None), ErrorCode::Unauthorized);
+        extend(&mut job, authority, limit + DAY, Some(payer)).unwrap();
+        assert_eq!(job.payers[0].deadline, Some(limit + DAY));
+    }
+
+    fn hook(program: Pubkey) -> UncheckedAccount<'static> {
+        let mut info = account(program, Pubkey::new_unique(), 1, Vec::new(), false);
+        info.executable = true;
+        UncheckedAccount::try_from(&*Box::leak(Box::new(info)))
+    }
+
+    // Completion hook events delivered so far, as (job, event, collected)
+    fn hook_calls(program: Pubkey) -> Vec<(Pubkey, CompletionEvent, u64)> {
+        INVOKED.with(|invoked| invoked.borrow().iter()
+            .filter(|ix| ix.program_id == program)
+            .map(|ix| {
+                assert_eq!(ix.data[..8], COMPLETION_HOOK_DISCRIMINATOR);
+                let args = CompletionHookArgs::try_from_slice(&ix.data[8..]).unwrap();
+                (args.job, args.event, args.collected)
+            })
+            .collect())
+    }
+
+    fn hooked_job(payers: &[Pubkey], program: Pubkey) -> PaymentJob {
+        let mut job = new_job(Pubkey::new_unique(), payers, &[Pubkey::new_unique()], 1_000, DAY);
+        job.completion_hook = Some(CompletionHook { program, accounts: Vec::new() });
+        job
+    }
+
+    #[test]
+    fn closing_an_unpaid_job_notifies_the_hook() {
+        let program = Pubkey::new_unique();
+        let mut job = job_account(&hooked_job(&[Pubkey::new_unique()], program), 0);
+        let cranker: &'static AccountInfo<'static> = Box::leak(Box::new(wallet(Pubkey::new_unique(), true)));
+
+        at(DAY);
+        distribute_with_hook(&mut job, cranker, Some(program)).unwrap();
+        assert!(job.closed);
+        assert!(hook_calls(program) == vec![(job.key(), CompletionEvent::Distributed, 0)]);
+    }
+
+    #[test]
+    fn cranks_may_skip_the_hook_after_the_grace_period() {
+        let program = Pubkey::new_unique();
+        let job = hooked_job(&[Pubkey::new_unique()], program);
+        let cranker: &'static AccountInfo<'static> = Box::leak(Box::new(wallet(Pubkey::new_unique(), true)));
+        let authority: &'static AccountInfo<'static> = Box::leak(Box::new(wallet(job.authority, true)));
+
+        at(DAY + HOOK_GRACE_PERIOD - 1);
+        assert_error(distribute(&mut job_account(&job, 0), cranker), ErrorCode::MissingAccount);
+
+        // The authority can clear the hook instead, so it is never skipped for them
+        at(DAY + HOOK_GRACE_PERIOD);
+        assert_error(distribute(&mut job_account(&job, 0), authority), ErrorCode::MissingAccount);
+        let mut job = job_account(&job, 0);
+        distribute(&mut job, cranker).unwrap();
+        assert!(job.closed);
+        assert!(hook_calls(program).is_empty());
+    }
+
+    #[test]
+    fn settling_the_last_payer_notifies_the_hook() {
+        at(0);
+        let program = Pubkey::new_unique();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut job = hooked_job(&[first, second], program);
+        job.payers[0].settled_off_chain = true;
+
+        // The hook program is required once the job is funded
+        let mut accounts = MarkSettled { job: job_account(&job, 0), authority: signer(job.authority, 0), hook_program: None };
+        assert_error(
+            crate::tab_pool::mark_settled(Context::new(&crate::ID, &mut accounts, &[], Default::default()), second, None),
+            ErrorCode::MissingAccount,
+        );
+        assert!(hook_calls(program).is_empty());
+
+        let job = job_account(&job, 0);
+        let mut accounts = MarkSettled { job: job.clone(), authority: signer(job.authority, 0), hook_program: Some(hook(program)) };
+        crate::tab_pool::mark_settled(Context::new(&crate::ID, &mut accounts, &[], Default::default()), second, None).unwrap();
+        assert!(hook_calls(program) == vec![(job.key(), CompletionEvent::Funded, 0)]);
+    }
+
+    #[test]
+    fn hook_payload_matches_an_anchor_instruction() {
+        let hash = anchor_lang::solana_program::hash::hash(b"global:on_tab_completed");
+        assert_eq!(COMPLETION_HOOK_DISCRIMINATOR, hash.to_bytes()[..8]);
+
+        // Job, event variant and collected amount, as the receiver decodes them
+        let job = Pubkey::new_unique();
+        let mut expected = job.to_bytes().to_vec();
+        expected.push(1);
+        expected.extend_from_slice(&1_500u64.to_le_bytes());
+        let args = CompletionHookArgs { job, event: CompletionEvent::Distributed, collected: 1_500 };
+        assert_eq!(args.try_to_vec().unwrap(), expected);
+    }
+
+    #[test]
+    fn late_payers_pay_the_late_fee() {
+        let (on_time, late) = (signer(Pubkey::new_unique(), 20_000), signer(Pubkey::new_unique(), 20_000));
+        let mut job = new_job(Pubkey::new_unique(), &[on_time.key(), late.key()], &[Pubkey::new_unique()], 10_000, DAY);
+        job.late_fee = Some(LateFee { kind: LateFeeKind::Percentage, value: 500, per_day: true });
+        job.payers[0].deadline = Some(3 * DAY);
+        let mut job = job_account(&job, 0);
+
+        // An extended deadline waives the fee, otherwise 5% per started day late
+        at(2 * DAY + 1);
+        pay(&mut job, &on_time).unwrap();
+        pay(&mut job, &late).unwrap();
+        assert_eq!(on_time.lamports(), 10_000);
+        assert_eq!(late.lamports(), 20_000 - 11_000);
+        assert!(!job.payers[0].paid_late && job.payers[1].paid_late);
+        assert_eq!(job.late_fees_collected, 1_000);
+        assert_eq!(job.collected(), 21_000);
+    }
+
+    fn settle_netted(jobs: &[AccountInfo<'static>], wallets: &[AccountInfo<'static>], transfers: Vec<NettedTransfer>) -> Result<()> {
+        let remaining: &'static [AccountInfo<'static>] = Box::leak([jobs, wallets].concat().into_boxed_slice());
+        let mut accounts = SettleNetted { system_program: system() };
+        crate::tab_pool::settle_netted(Context::new(&crate::ID, &mut accounts, remaining, Default::default()), jobs.len() as u8, transfers)
+    }
+
+    #[test]
+    fn netting_needs_every_participant_to_sign() {
+        at(0);
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let jobs = [
+            job_account(&new_job(Pubkey::new_unique(), &[alice], &[bob], 1_000, DAY), 0).to_account_info(),
+            job_account(&new_job(Pubkey::new_unique(), &[bob], &[alice], 600, DAY), 0).to_account_info(),
+        ];
+        let plan = vec![NettedTransfer { from: alice, to: bob, amount: 400 }];
+
+        // Bob's claim on Alice is netted away, so he must agree too
+        let unsigned = [account(alice, system_program::ID, 1_000, Vec::new(), true), wallet(bob, false)];
+        assert_error(settle_netted(&jobs, &unsigned, plan.clone()), ErrorCode::MissingSigner);
+        let wrong = vec![NettedTransfer { from: alice, to: bob, amount: 1_000 }];
+        let signed = [account(alice, system_program::ID, 1_000, Vec::new(), true), wallet(bob, true)];
+        assert_error(settle_netted(&jobs, &signed, wrong), ErrorCode::InvalidNettingPlan);
+
+        settle_netted(&jobs, &signed, plan).unwrap();
+        assert_eq!((signed[0].lamports(), signed[1].lamports()), (600, 400));
+        for job in jobs.iter() {
+            let job = PaymentJob::try_deserialize(&mut &job.try_borrow_data().unwrap()[..]).unwrap();
+            assert!(job.payers[0].settled_off_chain);
+            assert_eq!(job.payers[0].settlement_note.as_deref(), Some(NETTED_SETTLEMENT_NOTE));
+        }
+    }
+
+    fn claim_vested(job: &mut Account<'static, PaymentJob>, payee: &Signer<'static>) -> Result<()> {
+        let mut accounts = ClaimVested { job: job.clone(), payee: payee.clone() };
+        let result = crate::tab_pool::claim_vested(Context::new(&crate::ID, &mut accounts, &[], Default::default()));
+        *job = accounts.job;
+        result
+    }
+
+    #[test]
+    fn vesting_starts_paying_out_after_the_deadline() {
+        let payee = signer(Pubkey::new_unique(), 0);
+        let mut job = new_job(Pubkey::new_unique(), &[Pubkey::new_unique()], &[payee.key(), Pubkey::new_unique()], 1_000, 2 * DAY);
+        job.payers[0].paid = true;
+        job.vesting = Some(Vesting { start: 0, end: 10 * DAY, total: 0, claimed: vec![0, 0] });
+        let mut job = job_account(&job, 1_000);
+
+        at(DAY);
+        assert_error(claim_vested(&mut job, &payee), ErrorCode::BeforeDeadline);
+
+        // Half of the payee's 500 lamport share has vested halfway through
+        at(5 * DAY);
+        claim_vested(&mut job, &payee).unwrap();
+        assert_eq!(payee.lamports(), 250);
+        assert_eq!(job.vesting.as_ref().unwrap().total, 1_000);
+        assert_error(claim_vested(&mut job, &payee), ErrorCode::NothingToClaim);
+
+        at(10 * DAY);
+        claim_vested(&mut job, &payee).unwrap();
+        assert_eq!(payee.lamports(), 500);
+        assert_eq!(job.to_account_info().lamports(), 500);
+    }
+
+    #[test]
+    fn usd_jobs_charge_lamports_at_the_feed_price() {
+        let payer = signer(Pubkey::new_unique(), 1_000_000_000);
+        let feed_key = Pubkey::new_unique();
+        let mut job = new_job(Pubkey::new_unique(), &[payer.key(), Pubkey::new_unique()], &[Pubkey::new_unique()], 1_500, DAY);
+        job.usd_pricing = Some(UsdPricing { price_feed: feed_key, max_staleness: 60, max_confidence_bps: 100, collected: 0 });
+        let mut job = job_account(&job, 0);
+
+        // $150.00 per SOL, published at 1_000
+        let price = oracle::mock_price_account(15_000_000_000, 10_000_000, -8, 1_000);
+        let feed: &'static AccountInfo<'static> = Box::leak(Box::new(account(feed_key, Pubkey::new_unique(), 1, price.clone(), false)));
+        let other: &'static AccountInfo<'static> = Box::leak(Box::new(account(Pubkey::new_unique(), Pubkey::new_unique(), 1, price, false)));
+
+        at(1_000 + 61);
+        assert_error(pay_at_price(&mut job, &payer, None), ErrorCode::MissingAccount);
+        assert_error(pay_at_price(&mut job, &payer, Some(other)), ErrorCode::WrongPriceFeed);
+        assert_error(pay_at_price(&mut job, &payer, Some(feed)), ErrorCode::StalePrice);
+
+        // $15.00 is a tenth of a SOL
+        at(1_000 + 60);
+        pay_at_price(&mut job, &payer, Some(feed)).unwrap();
+        assert_eq!(payer.lamports(), 900_000_000);
+        assert_eq!(job.usd_pricing.as_ref().unwrap().collected, 100_000_000);
+    }
+}
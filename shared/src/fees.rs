@@ -0,0 +1,82 @@
+// Fee math shared between the on-chain program and the CLI
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// How a late fee is charged on top of the owed amount
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeBasis {
+    Flat(u64), // Fixed lamports
+    Bps(u64), // Basis points of the owed amount
+}
+
+// Number of started days between the deadline and now (0 when not late)
+pub fn days_late(deadline: i64, now: i64) -> u64 {
+    if now <= deadline {
+        return 0;
+    }
+    let seconds_late = now.saturating_sub(deadline) as u64;
+    seconds_late.div_ceil(SECONDS_PER_DAY as u64)
+}
+
+// Late fee owed for paying `amount` at `now`, None on overflow
+pub fn late_fee(basis: FeeBasis, per_day: bool, amount: u64, deadline: i64, now: i64) -> Option<u64> {
+    let days = days_late(deadline, now);
+    if days == 0 {
+        return Some(0);
+    }
+
+    let base = match basis {
+        FeeBasis::Flat(lamports) => lamports,
+        FeeBasis::Bps(bps) => {
+            let fee = (amount as u128).checked_mul(bps as u128)? / BPS_DENOMINATOR as u128;
+            u64::try_from(fee).ok()?
+        }
+    };
+
+    if per_day {
+        base.checked_mul(days)
+    } else {
+        Some(base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_late_counts_started_days() {
+        assert_eq!(days_late(1_000, 900), 0);
+        assert_eq!(days_late(1_000, 1_000), 0);
+        assert_eq!(days_late(1_000, 1_001), 1);
+        assert_eq!(days_late(1_000, 1_000 + SECONDS_PER_DAY), 1);
+        assert_eq!(days_late(1_000, 1_001 + SECONDS_PER_DAY), 2);
+    }
+
+    #[test]
+    fn no_fee_before_deadline() {
+        assert_eq!(late_fee(FeeBasis::Flat(500), true, 10_000, 1_000, 1_000), Some(0));
+        assert_eq!(late_fee(FeeBasis::Bps(100), false, 10_000, 1_000, 0), Some(0));
+    }
+
+    #[test]
+    fn flat_fee_charged_once_or_per_day() {
+        let now = 3 * SECONDS_PER_DAY - 1;
+        assert_eq!(late_fee(FeeBasis::Flat(500), false, 10_000, 0, now), Some(500));
+        assert_eq!(late_fee(FeeBasis::Flat(500), true, 10_000, 0, now), Some(1_500));
+    }
+
+    #[test]
+    fn bps_fee_rounds_down() {
+        // 2.5% of 1_999 is 49.975 lamports
+        assert_eq!(late_fee(FeeBasis::Bps(250), false, 1_999, 0, 1), Some(49));
+        assert_eq!(late_fee(FeeBasis::Bps(250), true, 1_999, 0, SECONDS_PER_DAY + 1), Some(98));
+    }
+
+    #[test]
+    fn overflow_is_none() {
+        assert_eq!(late_fee(FeeBasis::Flat(u64::MAX), true, 0, 0, SECONDS_PER_DAY + 1), None);
+        assert_eq!(late_fee(FeeBasis::Bps(20_000), false, u64::MAX, 0, 1), None);
+    }
+}
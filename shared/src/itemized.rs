@@ -0,0 +1,47 @@
+// Itemized bill math shared between the on-chain program and the CLI
+use crate::fees::BPS_DENOMINATOR;
+
+// Cost of `claim_bps` of an item bought `quantity` times at `price`, None on overflow
+pub fn portion_cost(price: u64, quantity: u16, claim_bps: u16) -> Option<u64> {
+    let total = price as u128 * quantity as u128;
+    u64::try_from(total * claim_bps as u128 / BPS_DENOMINATOR as u128).ok()
+}
+
+// Subtotal with tax and tip added, both in basis points of the subtotal
+pub fn with_surcharges(subtotal: u64, tax_bps: u16, tip_bps: u16) -> Option<u64> {
+    let surcharge = subtotal as u128 * (tax_bps as u128 + tip_bps as u128) / BPS_DENOMINATOR as u128;
+    subtotal.checked_add(u64::try_from(surcharge).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn portion_cost_of_whole_and_split_items() {
+        assert_eq!(portion_cost(1_000, 3, 10_000), Some(3_000));
+        assert_eq!(portion_cost(1_000, 3, 5_000), Some(1_500));
+        assert_eq!(portion_cost(1_000, 1, 0), Some(0));
+    }
+
+    #[test]
+    fn portion_cost_rounds_down() {
+        // A third of 100 lamports
+        assert_eq!(portion_cost(100, 1, 3_333), Some(33));
+        assert_eq!(portion_cost(1, 1, 9_999), Some(0));
+    }
+
+    #[test]
+    fn portion_cost_overflow_is_none() {
+        assert_eq!(portion_cost(u64::MAX, 2, 10_000), None);
+        assert_eq!(portion_cost(u64::MAX, 2, 5_000), Some(u64::MAX));
+    }
+
+    #[test]
+    fn surcharges_add_tax_and_tip() {
+        assert_eq!(with_surcharges(10_000, 0, 0), Some(10_000));
+        assert_eq!(with_surcharges(10_000, 800, 1_500), Some(12_300));
+        assert_eq!(with_surcharges(999, 800, 0), Some(1_078));
+        assert_eq!(with_surcharges(u64::MAX, 1, 0), None);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod fees;
+pub mod itemized;
+pub mod netting;
+pub mod oracle;
+pub mod pricing;
+pub mod vesting;
@@ -0,0 +1,173 @@
+// Debt netting across jobs, shared between the on-chain program and the CLI
+use std::collections::BTreeMap;
+
+// A gross debt from one wallet to another
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Obligation<K> {
+    pub from: K,
+    pub to: K,
+    pub amount: u64,
+}
+
+// A transfer in a netted settlement plan
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transfer<K> {
+    pub from: K,
+    pub to: K,
+    pub amount: u64,
+}
+
+// Split `total` into `parts` shares, giving remainder lamports to the first shares
+pub fn split_evenly(total: u64, parts: usize) -> Vec<u64> {
+    if parts == 0 {
+        return Vec::new();
+    }
+    let share = total / parts as u64;
+    let remainder = (total % parts as u64) as usize;
+    (0..parts).map(|i| if i < remainder { share + 1 } else { share }).collect()
+}
+
+// Net position of every wallet: positive when owed, negative when owing
+pub fn net_balances<K: Ord + Copy>(obligations: &[Obligation<K>]) -> BTreeMap<K, i128> {
+    let mut balances = BTreeMap::new();
+    for o in obligations {
+        *balances.entry(o.from).or_insert(0) -= o.amount as i128;
+        *balances.entry(o.to).or_insert(0) += o.amount as i128;
+    }
+    balances
+}
+
+// Whether applying `transfers` settles every net balance of `obligations` exactly
+pub fn settles<K: Ord + Copy>(obligations: &[Obligation<K>], transfers: &[Transfer<K>]) -> bool {
+    let mut balances = net_balances(obligations);
+    for t in transfers {
+        *balances.entry(t.from).or_insert(0) += t.amount as i128;
+        *balances.entry(t.to).or_insert(0) -= t.amount as i128;
+    }
+    balances.values().all(|b| *b == 0)
+}
+
+// Plan a small set of transfers that settles all obligations.
+// Exact debtor/creditor matches are paired first, then the largest debtor pays
+// the largest creditor until everyone is even, which needs at most n - 1 transfers.
+pub fn simplify<K: Ord + Copy>(obligations: &[Obligation<K>]) -> Vec<Transfer<K>> {
+    let balances = net_balances(obligations);
+    let mut debtors: Vec<(K, u128)> = balances.iter()
+        .filter(|(_, b)| **b < 0)
+        .map(|(k, b)| (*k, b.unsigned_abs()))
+        .collect();
+    let mut creditors: Vec<(K, u128)> = balances.iter()
+        .filter(|(_, b)| **b > 0)
+        .map(|(k, b)| (*k, b.unsigned_abs()))
+        .collect();
+
+    let mut transfers = Vec::new();
+
+    // Exact matches settle two wallets with a single transfer
+    debtors.retain(|(debtor, owed)| {
+        match creditors.iter().position(|(_, credit)| credit == owed) {
+            Some(pos) => {
+                let (creditor, _) = creditors.remove(pos);
+                transfers.push(Transfer { from: *debtor, to: creditor, amount: *owed as u64 });
+                false
+            }
+            None => true,
+        }
+    });
+
+    loop {
+        debtors.sort_by_key(|(_, owed)| std::cmp::Reverse(*owed));
+        creditors.sort_by_key(|(_, credit)| std::cmp::Reverse(*credit));
+        let (Some(debtor), Some(creditor)) = (debtors.first_mut(), creditors.first_mut()) else {
+            break;
+        };
+
+        let amount = debtor.1.min(creditor.1);
+        transfers.push(Transfer { from: debtor.0, to: creditor.0, amount: amount as u64 });
+        debtor.1 -= amount;
+        creditor.1 -= amount;
+
+        debtors.retain(|(_, owed)| *owed > 0);
+        creditors.retain(|(_, credit)| *credit > 0);
+    }
+
+    transfers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owe(from: u8, to: u8, amount: u64) -> Obligation<u8> {
+        Obligation { from, to, amount }
+    }
+
+    #[test]
+    fn split_evenly_gives_remainder_to_first_shares() {
+        assert_eq!(split_evenly(10, 3), vec![4, 3, 3]);
+        assert_eq!(split_evenly(9, 3), vec![3, 3, 3]);
+        assert_eq!(split_evenly(2, 3), vec![1, 1, 0]);
+        assert!(split_evenly(10, 0).is_empty());
+    }
+
+    #[test]
+    fn net_balances_sum_to_zero() {
+        let obligations = [owe(1, 2, 10), owe(2, 3, 4), owe(3, 1, 7)];
+        let balances = net_balances(&obligations);
+        assert_eq!(balances[&1], -3);
+        assert_eq!(balances[&2], 6);
+        assert_eq!(balances[&3], -3);
+        assert_eq!(balances.values().sum::<i128>(), 0);
+    }
+
+    #[test]
+    fn settles_rejects_partial_and_excess_plans() {
+        let obligations = [owe(1, 2, 10)];
+        assert!(settles(&obligations, &[Transfer { from: 1, to: 2, amount: 10 }]));
+        assert!(!settles(&obligations, &[]));
+        assert!(!settles(&obligations, &[Transfer { from: 1, to: 2, amount: 9 }]));
+        assert!(!settles(&obligations, &[Transfer { from: 1, to: 2, amount: 11 }]));
+    }
+
+    #[test]
+    fn cycles_cancel_out() {
+        let obligations = [owe(1, 2, 5), owe(2, 3, 5), owe(3, 1, 5)];
+        assert!(simplify(&obligations).is_empty());
+    }
+
+    #[test]
+    fn exact_matches_pair_up() {
+        // 1 owes 3 net 7 and 2 owes 4 net 9
+        let obligations = [owe(1, 3, 7), owe(2, 4, 4), owe(2, 4, 5)];
+        let plan = simplify(&obligations);
+        assert_eq!(plan.len(), 2);
+        assert!(plan.contains(&Transfer { from: 1, to: 3, amount: 7 }));
+        assert!(plan.contains(&Transfer { from: 2, to: 4, amount: 9 }));
+    }
+
+    #[test]
+    fn simplify_settles_with_at_most_n_minus_one_transfers() {
+        // Deterministic pseudo-random obligations between up to 8 wallets
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move |bound: u64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % bound
+        };
+
+        for _ in 0..500 {
+            let wallets = 2 + next(7) as u8;
+            let obligations: Vec<Obligation<u8>> = (0..1 + next(20))
+                .map(|_| owe(next(wallets as u64) as u8, next(wallets as u64) as u8, 1 + next(1_000)))
+                .collect();
+
+            let plan = simplify(&obligations);
+            assert!(settles(&obligations, &plan));
+            assert!(plan.iter().all(|t| t.amount > 0 && t.from != t.to));
+
+            let participants = net_balances(&obligations).values().filter(|b| **b != 0).count();
+            assert!(plan.len() <= participants.saturating_sub(1));
+        }
+    }
+}
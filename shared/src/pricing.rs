@@ -0,0 +1,41 @@
+// Price conversions shared between the on-chain program and the CLI
+
+// Converts `amount` at a rate of `numerator / denominator`, rounding up so the converted
+// amount never falls short, None on overflow or a zero denominator
+pub fn convert(amount: u64, numerator: u64, denominator: u64) -> Option<u64> {
+    if denominator == 0 {
+        return None;
+    }
+    let converted = (amount as u128 * numerator as u128).div_ceil(denominator as u128);
+    u64::try_from(converted).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_exact_rates() {
+        assert_eq!(convert(1_000, 3, 1), Some(3_000));
+        assert_eq!(convert(1_000, 1, 4), Some(250));
+        assert_eq!(convert(0, 7, 3), Some(0));
+    }
+
+    #[test]
+    fn rounds_up() {
+        // 10 at 1/3 is 3.33...
+        assert_eq!(convert(10, 1, 3), Some(4));
+        assert_eq!(convert(1, 1, 1_000_000), Some(1));
+    }
+
+    #[test]
+    fn zero_denominator_is_none() {
+        assert_eq!(convert(10, 1, 0), None);
+    }
+
+    #[test]
+    fn overflow_is_none() {
+        assert_eq!(convert(u64::MAX, 2, 1), None);
+        assert_eq!(convert(u64::MAX, u64::MAX, u64::MAX), Some(u64::MAX));
+    }
+}
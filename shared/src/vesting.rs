@@ -0,0 +1,45 @@
+// Linear vesting math shared between the on-chain program and the CLI
+
+// Portion of `total` vested at `now` for a schedule running from `start` to `end`
+pub fn vested_amount(total: u64, start: i64, end: i64, now: i64) -> u64 {
+    if now <= start {
+        return 0;
+    }
+    if now >= end || end <= start {
+        return total;
+    }
+    let elapsed = (now - start) as u128;
+    let duration = (end - start) as u128;
+    (total as u128 * elapsed / duration) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_vests_before_start() {
+        assert_eq!(vested_amount(1_000, 100, 200, 50), 0);
+        assert_eq!(vested_amount(1_000, 100, 200, 100), 0);
+    }
+
+    #[test]
+    fn vests_linearly_rounding_down() {
+        assert_eq!(vested_amount(1_000, 100, 200, 150), 500);
+        assert_eq!(vested_amount(1_000, 0, 3, 1), 333);
+        assert_eq!(vested_amount(1_000, 0, 3, 2), 666);
+        assert_eq!(vested_amount(u64::MAX, 0, 2, 1), u64::MAX / 2);
+    }
+
+    #[test]
+    fn fully_vested_at_end() {
+        assert_eq!(vested_amount(1_000, 100, 200, 200), 1_000);
+        assert_eq!(vested_amount(1_000, 100, 200, 10_000), 1_000);
+    }
+
+    #[test]
+    fn empty_schedule_vests_at_start() {
+        assert_eq!(vested_amount(1_000, 100, 100, 100), 0);
+        assert_eq!(vested_amount(1_000, 100, 100, 101), 1_000);
+    }
+}